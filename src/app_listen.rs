@@ -0,0 +1,218 @@
+use std::time::Duration;
+use serde_json::{Map, Value};
+use crate::application::{Application, ControlFlowMode};
+use crate::listen::{send_io_message, send_io_error};
+use crate::tray::{create_tray, update_tray};
+use crate::menu::{build_menu, update_menu_item};
+use crate::notification::show_notification;
+use crate::shortcut::get_shortcut_params;
+use crate::platform::Platform;
+
+// 应用级消息使用的label，不属于任何窗口
+pub const APP_LABEL: &str = "@app";
+
+// 获取事件循环的运行模式
+pub fn get_control_flow(obj: &Value) -> Result<ControlFlowMode, String> {
+  match obj.get("mode").and_then(|v| v.as_str()) {
+    Some("wait") => Ok(ControlFlowMode::Wait),
+    Some("poll") => Ok(ControlFlowMode::Poll),
+    Some("waitUntil") => {
+      let interval = obj.get("interval").and_then(|v| v.as_u64()).ok_or("waitUntil模式必须设置interval")?;
+      if interval == 0 {
+        return Err("interval必须大于0".to_string());
+      }
+      Ok(ControlFlowMode::Interval(Duration::from_millis(interval)))
+    },
+    _ => Err(format!("无效的事件循环模式: {}", obj))
+  }
+}
+
+// 处理不属于任何窗口的应用级消息
+pub fn handle_app_listen(app: &mut Application, method: &str, data: &Value, mut response: Map<String, Value>, platform: &dyn Platform) {
+  match method {
+    "set_control_flow" => {
+      match get_control_flow(data) {
+        Ok(mode) => {
+          app.set_control_flow(mode);
+          send_io_message(Value::Object(response));
+        },
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "create_tray" => {
+      let Some(attr) = data.as_object() else {
+        send_io_error(response, "托盘参数必须为对象".to_string());
+        return;
+      };
+      let Some(id) = attr.get("id").and_then(|v| v.as_str()).map(|id| id.to_string()) else {
+        send_io_error(response, "托盘必须设置id".to_string());
+        return;
+      };
+      if app.trays.contains_key(&id) {
+        send_io_error(response, format!("托盘 {} 已存在", id));
+        return;
+      }
+      match create_tray(platform, &id, attr, &mut app.menu_items) {
+        Ok(tray) => {
+          app.trays.insert(id, tray);
+          send_io_message(Value::Object(response));
+        },
+        Err(e) => {
+          // 托盘创建失败时菜单已经创建，清理其中的菜单项
+          app.retain_menu_items();
+          send_io_error(response, e);
+        }
+      }
+    },
+    "update_tray" => {
+      let Some(attr) = data.as_object() else {
+        send_io_error(response, "托盘参数必须为对象".to_string());
+        return;
+      };
+      let id = attr.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+      let result = match app.trays.get_mut(id) {
+        Some(tray) => update_tray(tray, attr, &mut app.menu_items),
+        None => Err(format!("托盘 {} 不存在", id))
+      };
+      // 托盘菜单被替换或移除后清理其中的菜单项
+      if attr.contains_key("menu") {
+        app.retain_menu_items();
+      }
+      match result {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "remove_tray" => {
+      let id = data.as_str().unwrap_or_default();
+      // 托盘图标在drop时从系统托盘移除
+      match app.trays.remove(id) {
+        Some(_) => {
+          app.retain_menu_items();
+          send_io_message(Value::Object(response));
+        },
+        None => send_io_error(response, format!("托盘 {} 不存在", id))
+      }
+    },
+    "set_app_menu" => {
+      let menu = match data {
+        Value::Null => Ok(None),
+        items => build_menu(items, &mut app.menu_items).map(Some)
+      };
+      match menu {
+        Ok(menu) => {
+          app.set_app_menu(menu);
+          send_io_message(Value::Object(response));
+        },
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "update_menu_item" => {
+      let Some(attr) = data.as_object() else {
+        send_io_error(response, "菜单项参数必须为对象".to_string());
+        return;
+      };
+      let id = attr.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+      let result = match app.menu_items.get(id) {
+        Some(item) => update_menu_item(item, attr),
+        None => Err(format!("菜单项 {} 不存在", id))
+      };
+      match result {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "register_shortcut" => {
+      let (id, accelerator, release_event) = match get_shortcut_params(data) {
+        Ok(params) => params,
+        Err(e) => {
+          send_io_error(response, e);
+          return;
+        }
+      };
+      match app.shortcuts.register(id.clone(), &accelerator, release_event) {
+        Ok(_) => {
+          response.insert("data".to_string(), Value::String(id));
+          send_io_message(Value::Object(response));
+        },
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "unregister_shortcut" => {
+      match app.shortcuts.unregister(data.as_str().unwrap_or_default()) {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "unregister_all_shortcuts" => {
+      match app.shortcuts.unregister_all() {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "read_clipboard_text" | "read_clipboard_html" | "read_clipboard_image" => {
+      let result = match method {
+        "read_clipboard_text" => app.clipboard.read_text(),
+        "read_clipboard_html" => app.clipboard.read_html(),
+        _ => app.clipboard.read_image()
+      };
+      match result {
+        Ok(content) => {
+          response.insert("data".to_string(), content);
+          send_io_message(Value::Object(response));
+        },
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "write_clipboard_text" => {
+      match app.clipboard.write_text(data.as_str().unwrap_or_default()) {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "write_clipboard_html" => {
+      let Some(html) = data.get("html").and_then(|v| v.as_str()) else {
+        send_io_error(response, "必须设置html".to_string());
+        return;
+      };
+      match app.clipboard.write_html(html, data.get("altText").and_then(|v| v.as_str())) {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "write_clipboard_image" => {
+      match app.clipboard.write_image(data.as_str().unwrap_or_default()) {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "clear_clipboard" => {
+      match app.clipboard.clear() {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "notify" => {
+      show_notification(platform, data, response);
+    },
+    "register_url_scheme" => {
+      match platform.register_url_scheme(data) {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    "unregister_url_scheme" => {
+      match platform.unregister_url_scheme(data) {
+        Ok(_) => send_io_message(Value::Object(response)),
+        Err(e) => send_io_error(response, e)
+      }
+    },
+    // 不属于任何窗口的对话框
+    "open_file_dialog" | "save_file_dialog" | "pick_folder" | "message_dialog" => {
+      platform.show_dialog(method, data, response);
+    },
+    _ => {
+      send_io_error(response, format!("方法 {} 不存在", method));
+    }
+  }
+}
@@ -1,23 +1,66 @@
 use std::collections::HashMap;
 use winit::application::ApplicationHandler;
 use winit::event::WindowEvent;
-use winit::event_loop::{ ActiveEventLoop, EventLoop, EventLoopProxy};
+use winit::event_loop::{ ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
 use winit::window::{WindowAttributes, WindowId };
-use wry::dpi::{LogicalPosition, LogicalSize};
-use wry::{Rect, WebViewAttributes, WebViewBuilder};
+use wry::dpi::LogicalSize;
+use wry::{Rect, WebContext, WebViewAttributes};
 use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::rc::Rc;
 use std::thread;
+use std::time::{Duration, Instant};
 use crate::window::Window;
+use crate::webview::{ChildWebview, Layout};
+use crate::platform::{NativeWindow, NativeWebview, Platform};
 use crate::listen::{IO_CHANNEL_PREFIX, handle_listen};
-use crate::event::handle_window_event;
+use crate::event::{handle_window_event, send_window_event, send_app_event};
+use crate::download::{handle_download_started, handle_download_completed, set_download_policy};
+use crate::hittest::{handle_hittest_message, update_region_hittest};
+use crate::menu::{listen_menu_events, init_for_app, remove_for_app, retain_menu_items, MenuItems};
+use muda::Menu;
+use crate::tray::{listen_tray_events, Tray};
+use crate::shortcut::{listen_shortcut_events, Shortcuts};
+use crate::clipboard::Clipboard;
+use crate::script::{resolve_script, expire_scripts};
+
+// 页面设置了可点击区域时查询光标位置的间隔
+const HITTEST_POLL_INTERVAL: Duration = Duration::from_millis(16);
 
 pub enum Action {
-  ForwardMessage(String)
+  ForwardMessage(String),
+  // 页面通过window.ipc.postMessage发送的消息，参数为窗口label、子webview的id(主webview为None)和消息内容
+  IpcMessage(String, Option<String>, String)
+}
+
+// 事件循环的运行模式
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControlFlowMode {
+  // 没有事件时休眠
+  Wait,
+  // 没有事件时也持续运行
+  Poll,
+  // 按间隔唤醒并发送tick事件
+  Interval(Duration)
 }
 
 pub struct Application {
   is_resumed: bool,
+  control_flow: ControlFlowMode,
+  // 下一次发送tick事件的时间
+  next_tick: Option<Instant>,
   pub windows: HashMap<String, Window>,
+  // 以数据目录为key的webview上下文，使用相同目录的窗口共享同一个上下文
+  pub web_contexts: HashMap<PathBuf, WebContext>,
+  // 以id为key的托盘图标
+  pub trays: HashMap<String, Tray>,
+  // 设置了id的菜单项，用于运行时修改菜单项
+  pub menu_items: MenuItems,
+  // 应用菜单，macOS上显示在屏幕顶部，Windows上显示在没有单独设置菜单的窗口中
+  pub app_menu: Option<Menu>,
+  pub shortcuts: Shortcuts,
+  pub clipboard: Clipboard,
+  // 模拟模式中没有事件循环，为None
   pub proxy: Option<EventLoopProxy<Action>>
 }
 
@@ -25,12 +68,27 @@ impl Application {
   pub fn new () -> Self {
     Self {
       is_resumed: false,
+      control_flow: ControlFlowMode::Wait,
+      next_tick: None,
       windows: HashMap::new(),
+      web_contexts: HashMap::new(),
+      trays: HashMap::new(),
+      menu_items: HashMap::new(),
+      app_menu: None,
+      shortcuts: Shortcuts::default(),
+      clipboard: Clipboard::default(),
       proxy: None
     }
   }
   pub fn run(&mut self) {
-    let event_loop = EventLoop::<Action>::with_user_event().build().unwrap();
+    let mut builder = EventLoop::<Action>::with_user_event();
+    // Windows上需要在消息循环中处理窗口菜单的快捷键
+    #[cfg(target_os = "windows")]
+    {
+      use winit::platform::windows::EventLoopBuilderExtWindows;
+      builder.with_msg_hook(crate::menu::translate_accelerator);
+    }
+    let event_loop = builder.build().unwrap();
     let proxy = event_loop.create_proxy();
     self.proxy = Some(proxy);
     let _ = event_loop.run_app(self);
@@ -61,38 +119,169 @@ impl Application {
     self.windows.get(&label).clone()
   }
   pub fn close_window(&mut self, label: String){
-    self.windows.remove(&label);
+    // 先关闭以该窗口为父窗口的子窗口
+    let children: Vec<String> = self.windows.values()
+      .filter(|w| w.parent.as_ref() == Some(&label))
+      .map(|w| w.label.clone())
+      .collect();
+    for child in children {
+      send_window_event(&child, "close", None);
+      self.close_window(child);
+    }
+    set_download_policy(&label, None);
+    if let Some(window) = self.windows.remove(&label) {
+      window.save_state();
+      if let Some(menu) = window.menu.as_ref().or(self.app_menu.as_ref()) {
+        let _ = window.window.remove_menu(menu);
+      }
+      // 模态窗口关闭后恢复父窗口的输入
+      if window.modal {
+        if let Some(parent) = window.parent.and_then(|p| self.windows.get(&p)) {
+          parent.set_enable(true);
+          parent.focus_window();
+        }
+      }
+      if window.menu.is_some() {
+        self.retain_menu_items();
+      }
+    }
+  }
+  // 设置窗口的父窗口，模态窗口会禁用父窗口的输入直到该窗口关闭
+  pub fn set_window_parent(&mut self, label: &str, parent: String, modal: bool) {
+    if modal {
+      if let Some(parent) = self.windows.get(&parent) {
+        parent.set_enable(false);
+      }
+    }
+    if let Some(window) = self.windows.get_mut(label) {
+      window.parent = Some(parent);
+      window.modal = modal;
+    }
+  }
+  // 设置窗口的菜单栏，为None时恢复为应用菜单
+  pub fn set_window_menu(&mut self, label: &str, menu: Option<Menu>) -> Result<(), String> {
+    let result = self.apply_window_menu(label, menu);
+    self.retain_menu_items();
+    result
+  }
+  fn apply_window_menu(&mut self, label: &str, menu: Option<Menu>) -> Result<(), String> {
+    let window = self.windows.get(label).ok_or(format!("窗口 {} 不存在", label))?;
+    if let Some(current) = window.menu.as_ref().or(self.app_menu.as_ref()) {
+      let _ = window.window.remove_menu(current);
+    }
+    match (&menu, &self.app_menu) {
+      (Some(menu), _) => window.window.init_menu(menu)?,
+      (None, Some(app_menu)) => {
+        let _ = window.window.init_menu(app_menu);
+      },
+      (None, None) => {}
+    }
+    self.windows.get_mut(label).unwrap().menu = menu;
+    Ok(())
+  }
+  // 设置应用菜单，为None时移除
+  pub fn set_app_menu(&mut self, menu: Option<Menu>) {
+    // 没有单独设置菜单的窗口使用应用菜单
+    let windows: Vec<_> = self.windows.values().filter(|w| w.menu.is_none()).map(|w| w.window.clone()).collect();
+    if let Some(current) = self.app_menu.take() {
+      remove_for_app(&current);
+      for window in &windows {
+        let _ = window.remove_menu(&current);
+      }
+    }
+    if let Some(menu) = &menu {
+      init_for_app(menu);
+      for window in &windows {
+        let _ = window.init_menu(menu);
+      }
+    }
+    self.app_menu = menu;
+    self.retain_menu_items();
+  }
+  // 移除已替换或已移除的菜单中的菜单项，只保留应用菜单、窗口菜单和托盘菜单中的
+  pub fn retain_menu_items(&mut self) {
+    let menus = self.app_menu.iter()
+      .chain(self.windows.values().filter_map(|w| w.menu.as_ref()))
+      .chain(self.trays.values().filter_map(|t| t.menu.as_ref()));
+    retain_menu_items(&mut self.menu_items, menus);
   }
-  pub fn create_new_window(&mut self, event_loop: &ActiveEventLoop, label: String, mut window_attr:WindowAttributes, webview_attr:WebViewAttributes) -> WindowId {
+  // 设置事件循环的运行模式
+  pub fn set_control_flow(&mut self, mode: ControlFlowMode) {
+    self.control_flow = mode;
+    self.next_tick = None;
+  }
+  pub fn control_flow(&self) -> ControlFlowMode {
+    self.control_flow
+  }
+  // 处理页面通过ipc发送的消息
+  fn handle_ipc_message(&mut self, label: &str, webview: Option<&str>, message: &str) {
+    let Ok(message) = serde_json::from_str::<serde_json::Value>(message) else {
+      return;
+    };
+    if resolve_script(label, webview, &message) {
+      return;
+    }
+    // 只处理开启了hittestRegions的窗口的主webview发送的鼠标穿透消息
+    if let Some(window) = self.windows.get_mut(label).filter(|w| w.page_hittest && webview.is_none()) {
+      handle_hittest_message(window, &message);
+    }
+  }
+  // 获取父窗口的模态子窗口
+  pub fn modal_child(&self, label: &str) -> Option<&Window> {
+    self.windows.values().find(|w| w.modal && w.parent.as_deref() == Some(label))
+  }
+  // 在窗口内创建webview
+  fn build_webview(&mut self, window: &dyn NativeWindow, label: &str, webview_id: Option<String>, webview_attr: WebViewAttributes, bounds: Rect, data_directory: Option<PathBuf>) -> Result<Box<dyn NativeWebview>, String> {
+    // 数据目录的上下文借用自self，重新绑定使属性的生命周期不超过这次借用
+    let mut webview_attr: WebViewAttributes = webview_attr;
+    // 指定了数据目录的窗口使用独立的cookie、localStorage和缓存
+    if let Some(data_directory) = data_directory {
+      let context = self.web_contexts
+        .entry(data_directory.clone())
+        .or_insert_with(|| WebContext::new(Some(data_directory)));
+      webview_attr.context = Some(context);
+    }
+    webview_attr.bounds = Some(bounds);
+    // 下载事件转发给node端处理
+    let download_label = label.to_string();
+    let completed_label = label.to_string();
+    webview_attr.download_started_handler = Some(Box::new(move |url, path| handle_download_started(&download_label, url, path)));
+    webview_attr.download_completed_handler = Some(Rc::new(move |url, path, success| handle_download_completed(&completed_label, url, path, success)));
+    // ipc消息需要修改窗口状态，通过事件循环转发给主线程处理
+    if let Some(ipc_proxy) = self.proxy.clone() {
+      let ipc_label = label.to_string();
+      webview_attr.ipc_handler = Some(Box::new(move |request| {
+        let _ = ipc_proxy.send_event(Action::IpcMessage(ipc_label.clone(), webview_id.clone(), request.into_body()));
+      }));
+    }
+    window.build_webview(webview_attr)
+  }
+  // 创建窗口和主webview，任一步失败时返回错误，已创建的窗口会被销毁
+  pub fn create_new_window(&mut self, platform: &dyn Platform, label: String, mut window_attr:WindowAttributes, webview_attr:WebViewAttributes, data_directory: Option<PathBuf>, layout: Layout) -> Result<WindowId, String> {
     if window_attr.inner_size.is_none() {
       window_attr = window_attr.with_inner_size(LogicalSize::new(800, 600));
     }
     let size = window_attr.inner_size.unwrap();
-    let window = event_loop.create_window(window_attr).unwrap();
-    // 直接使用WebViewBuilder::new()创建的webview会导致winit窗口崩溃，需要创建child webview
-    let mut webview_uilder = WebViewBuilder::new_as_child(&window)
-      .with_bounds(Rect{
-        position: LogicalPosition::new(0.0, 0.0).into(),
-        size: size,
-      });
-    if webview_attr.url.is_some() {
-      webview_uilder = webview_uilder.with_url(webview_attr.url.unwrap());
-    }
-    if webview_attr.background_color.is_some() {
-      webview_uilder = webview_uilder.with_background_color(webview_attr.background_color.unwrap());
-    }
-    if webview_attr.html.is_some() {
-      webview_uilder = webview_uilder.with_html(webview_attr.html.unwrap());
-    }
-    webview_uilder = webview_uilder
-      .with_transparent(webview_attr.transparent)
-      .with_devtools(webview_attr.devtools)
-      .with_autoplay(webview_attr.autoplay);
-
-    let webview = webview_uilder.build().unwrap();
+    let window = platform.create_window(window_attr).map_err(|e| format!("创建窗口错误: {}", e))?;
+    let bounds = layout.to_rect(size.to_logical(window.scale_factor()));
+    let visible = webview_attr.visible;
+    let webview = self.build_webview(window.as_ref(), &label, None, webview_attr, bounds, data_directory)
+      .map_err(|e| format!("创建webview错误: {}", e))?;
     let id = window.id();
-    self.windows.insert(label.clone(), Window::new(label, window, webview, id));
-    id
+    self.windows.insert(label.clone(), Window::new(label, window, webview, id, layout, visible));
+    Ok(id)
+  }
+  // 在已有窗口内添加子webview
+  pub fn add_child_webview(&mut self, label: String, id: String, webview_attr: WebViewAttributes, layout: Layout, data_directory: Option<PathBuf>) -> Result<(), String> {
+    let window = self.windows.get(&label).ok_or(format!("窗口 {} 不存在", label))?.clone();
+    if window.child(&id).is_some() {
+      return Err(format!("webview {} 已存在", id));
+    }
+    let visible = webview_attr.visible;
+    let bounds = layout.to_rect(window.logical_size());
+    let webview = self.build_webview(window.window.as_ref(), &label, Some(id.clone()), webview_attr, bounds, data_directory)?;
+    self.windows.get_mut(&label).unwrap().add_child(ChildWebview::new(id, webview, layout, visible));
+    Ok(())
   }
 }
 
@@ -102,12 +291,18 @@ impl ApplicationHandler<Action> for Application {
       self.is_resumed = true;
       // 程序启动时启动监听node进程发送过来的消息
       self.listen();
+      listen_menu_events();
+      listen_tray_events();
+      listen_shortcut_events();
     }
   }
   fn user_event(&mut self, event_loop: &ActiveEventLoop, event: Action) {
     match event {
       Action::ForwardMessage(string) => {
-        handle_listen(self, string.as_str(), &event_loop);
+        handle_listen(self, string.as_str(), event_loop);
+      },
+      Action::IpcMessage(label, webview, message) => {
+        self.handle_ipc_message(&label, webview.as_deref(), &message);
       }
     }
   }
@@ -119,9 +314,39 @@ impl ApplicationHandler<Action> for Application {
   ) {
     handle_window_event(self, event_loop, window_id, event);
   }
-  // fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-  //   use std::time;
-  //   const WAIT_TIME: time::Duration = time::Duration::from_millis(100);
-  //   event_loop.set_control_flow(ControlFlow::WaitUntil(time::Instant::now() + WAIT_TIME));
-  // }
+  fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+    // 输入事件、IO消息和可点击区域的轮询都会唤醒事件循环，每次唤醒都检查是否到了tick的时间
+    if self.next_tick.is_some_and(|next_tick| Instant::now() >= next_tick) {
+      self.next_tick = None;
+      send_app_event("tick", None);
+    }
+    // 鼠标穿透时收不到光标事件，设置了可点击区域的窗口需要定时查询光标位置，没有这样的窗口时不轮询
+    let mut polling = false;
+    for window in self.windows.values_mut() {
+      if window.hittest_regions.is_none() {
+        continue;
+      }
+      if update_region_hittest(window) {
+        polling = true;
+      }else{
+        // 查询不到光标位置时停止区域穿透，避免一直轮询
+        window.hittest_regions = None;
+        let _ = window.set_cursor_hittest(true);
+      }
+    }
+    let hittest_deadline = polling.then(|| Instant::now() + HITTEST_POLL_INTERVAL);
+    // 移动或缩放停止后写入窗口状态
+    let state_deadline = self.windows.values().filter_map(|window| window.save_state_if_due()).min();
+    // 等待结果的脚本需要在超时的时间唤醒事件循环
+    let deadline = hittest_deadline.into_iter().chain(state_deadline).chain(expire_scripts()).min();
+    let control_flow = match self.control_flow {
+      ControlFlowMode::Poll => ControlFlow::Poll,
+      ControlFlowMode::Wait => deadline.map_or(ControlFlow::Wait, ControlFlow::WaitUntil),
+      ControlFlowMode::Interval(interval) => {
+        let next_tick = *self.next_tick.get_or_insert_with(|| Instant::now() + interval);
+        ControlFlow::WaitUntil(deadline.map_or(next_tick, |deadline| deadline.min(next_tick)))
+      }
+    };
+    event_loop.set_control_flow(control_flow);
+  }
 }
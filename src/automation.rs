@@ -0,0 +1,190 @@
+use std::time::Duration;
+use serde_json::{json, Map, Value};
+use crate::window::Window;
+use crate::script::{run_script, get_timeout};
+use crate::listen::send_io_error;
+
+// 自动化操作的默认超时时间
+const AUTOMATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+// 自动化操作共用的页面函数
+// info返回元素信息，位置和尺寸为相对于webview的css像素
+const HELPERS: &str = r#"
+    var query = function (selector) {
+      var element = document.querySelector(selector);
+      if (!element) throw new Error('找不到元素 ' + selector);
+      return element;
+    };
+    var isVisible = function (element) {
+      var rect = element.getBoundingClientRect();
+      var style = window.getComputedStyle(element);
+      return rect.width > 0 && rect.height > 0 && style.visibility !== 'hidden' && style.display !== 'none';
+    };
+    var info = function (element) {
+      var rect = element.getBoundingClientRect();
+      var attributes = {};
+      Array.prototype.forEach.call(element.attributes, function (attr) { attributes[attr.name] = attr.value; });
+      return {
+        tagName: element.tagName.toLowerCase(),
+        text: element.innerText !== undefined ? element.innerText : element.textContent,
+        value: typeof element.value === 'string' ? element.value : null,
+        visible: isVisible(element),
+        rect: { x: rect.left, y: rect.top, width: rect.width, height: rect.height },
+        attributes: attributes
+      };
+    };
+"#;
+
+// 查找元素，找不到时返回null
+const FIND_ELEMENT: &str = r#"
+    var element = document.querySelector(args.selector);
+    return element ? info(element) : null;
+"#;
+
+// 在元素中心依次触发按下、抬起和点击事件
+const CLICK_ELEMENT: &str = r#"
+    var element = query(args.selector);
+    element.scrollIntoView({ block: 'center', inline: 'center' });
+    var rect = element.getBoundingClientRect();
+    var init = { bubbles: true, cancelable: true, view: window, button: 0, clientX: rect.left + rect.width / 2, clientY: rect.top + rect.height / 2 };
+    var pointer = typeof PointerEvent === 'function' ? PointerEvent : MouseEvent;
+    element.dispatchEvent(new pointer('pointerdown', init));
+    element.dispatchEvent(new MouseEvent('mousedown', init));
+    if (typeof element.focus === 'function') element.focus();
+    element.dispatchEvent(new pointer('pointerup', init));
+    element.dispatchEvent(new MouseEvent('mouseup', init));
+    element.click();
+    return null;
+"#;
+
+// 逐个字符输入，输入框通过原生setter修改value，让框架的受控组件也能收到修改
+const TYPE_TEXT: &str = r#"
+    var element = query(args.selector);
+    var editable = element.isContentEditable;
+    if (!editable && typeof element.value !== 'string') throw new Error('元素 ' + args.selector + ' 不可输入');
+    var setValue = function (value) {
+      var descriptor = Object.getOwnPropertyDescriptor(Object.getPrototypeOf(element), 'value');
+      if (descriptor && descriptor.set) descriptor.set.call(element, value); else element.value = value;
+    };
+    element.focus();
+    if (args.clear) {
+      if (editable) element.textContent = ''; else setValue('');
+      element.dispatchEvent(new Event('input', { bubbles: true }));
+    }
+    Array.from(args.text).forEach(function (char) {
+      var init = { key: char, bubbles: true, cancelable: true };
+      var allowed = element.dispatchEvent(new KeyboardEvent('keydown', init));
+      if (allowed && element.dispatchEvent(new KeyboardEvent('keypress', init))) {
+        if (editable) {
+          document.execCommand('insertText', false, char);
+        } else {
+          setValue(element.value + char);
+          element.dispatchEvent(new InputEvent('input', { bubbles: true, data: char, inputType: 'insertText' }));
+        }
+      }
+      element.dispatchEvent(new KeyboardEvent('keyup', init));
+    });
+    if (!editable) element.dispatchEvent(new Event('change', { bubbles: true }));
+    return null;
+"#;
+
+// 获取元素的文本
+const ELEMENT_TEXT: &str = r#"
+    var element = query(args.selector);
+    return element.innerText !== undefined ? element.innerText : element.textContent;
+"#;
+
+// 轮询等待元素出现，设置visible时等待元素可见
+const WAIT_FOR_SELECTOR: &str = r#"
+    return new Promise(function (resolve, reject) {
+      var interval, timer;
+      var check = function () {
+        var element = document.querySelector(args.selector);
+        if (!element || (args.visible && !isVisible(element))) return false;
+        clearInterval(interval);
+        clearTimeout(timer);
+        resolve(info(element));
+        return true;
+      };
+      if (check()) return;
+      interval = setInterval(check, 100);
+      timer = setTimeout(function () {
+        clearInterval(interval);
+        reject(new Error('等待元素 ' + args.selector + ' 超时'));
+      }, args.timeout);
+    });
+"#;
+
+// 生成操作对应的页面脚本，参数以json形式写入脚本
+fn build_script(method: &str, args: &Value) -> Result<String, String> {
+  let body = match method {
+    "find_element" => FIND_ELEMENT,
+    "click_element" => CLICK_ELEMENT,
+    "type_text" => TYPE_TEXT,
+    "get_element_text" => ELEMENT_TEXT,
+    "wait_for_selector" => WAIT_FOR_SELECTOR,
+    _ => return Err(format!("方法 {} 不存在", method))
+  };
+  Ok(format!("    var args = {};{}{}", args, HELPERS, body))
+}
+
+// 检查自动化操作的参数，返回写入页面脚本的参数和超时时间
+// { selector, text, clear, visible, timeout }
+pub fn get_automation_args(method: &str, data: &Value) -> Result<(Value, Duration), String> {
+  let selector = data.get("selector").and_then(|v| v.as_str()).ok_or("必须设置selector")?;
+  if method == "type_text" && !data.get("text").is_some_and(|v| v.is_string()) {
+    return Err("必须设置text".to_string());
+  }
+  let timeout = get_timeout(data, AUTOMATION_TIMEOUT)?;
+  let args = json!({
+    "selector": selector,
+    "text": data.get("text").and_then(|v| v.as_str()).unwrap_or_default(),
+    "clear": data.get("clear").and_then(|v| v.as_bool()).unwrap_or(false),
+    "visible": data.get("visible").and_then(|v| v.as_bool()).unwrap_or(false),
+    "timeout": timeout.as_millis() as u64
+  });
+  Ok((args, timeout))
+}
+
+// 通过注入脚本在窗口的主webview中操作元素
+pub fn handle_automation(window: &Window, method: &str, data: &Value, response: Map<String, Value>) {
+  let script = get_automation_args(method, data)
+    .and_then(|(args, timeout)| build_script(method, &args).map(|script| (script, args, timeout)));
+  let (script, args, timeout) = match script {
+    Ok(script) => script,
+    Err(e) => {
+      send_io_error(response, e);
+      return;
+    }
+  };
+  let timeout_error = match method {
+    "wait_for_selector" => format!("等待元素 {} 超时", args["selector"].as_str().unwrap_or_default()),
+    _ => "执行脚本超时".to_string()
+  };
+  run_script(window, &script, response, timeout, timeout_error);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn automation_args_are_checked() {
+    let (args, timeout) = get_automation_args("click_element", &json!({ "selector": "#ok", "timeout": 100 })).unwrap();
+    assert_eq!(timeout, Duration::from_millis(100));
+    assert_eq!(args, json!({ "selector": "#ok", "text": "", "clear": false, "visible": false, "timeout": 100 }));
+    assert_eq!(get_automation_args("find_element", &json!({})).err(), Some("必须设置selector".to_string()));
+    assert_eq!(get_automation_args("type_text", &json!({ "selector": "input" })).err(), Some("必须设置text".to_string()));
+    let (_, timeout) = get_automation_args("find_element", &json!({ "selector": "a" })).unwrap();
+    assert_eq!(timeout, AUTOMATION_TIMEOUT);
+    assert_eq!(get_automation_args("find_element", &json!({ "selector": "a", "timeout": 0 })).err(), Some("timeout必须为大于0的整数".to_string()));
+  }
+
+  #[test]
+  fn script_embeds_args_as_json() {
+    let args = json!({ "selector": "a[title=\"'\"]" });
+    let script = build_script("find_element", &args).unwrap();
+    assert!(script.starts_with(&format!("    var args = {};", args)));
+    assert_eq!(build_script("remove", &args).err(), Some("方法 remove 不存在".to_string()));
+  }
+}
@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+use std::io::Cursor;
+use serde_json::Value;
+use arboard::{Clipboard as ArboardClipboard, Error, ImageData};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::{ImageFormat, RgbaImage};
+use crate::platform::NativeClipboard;
+
+// 剪贴板中没有对应格式的内容时返回null
+fn get_content<T: Into<Value>>(result: Result<T, Error>) -> Result<Value, String> {
+  match result {
+    Ok(content) => Ok(content.into()),
+    Err(Error::ContentNotAvailable) => Ok(Value::Null),
+    Err(e) => Err(e.to_string())
+  }
+}
+
+// 剪贴板图片编码为base64的PNG
+fn encode_png(image: ImageData) -> Result<String, String> {
+  let image = RgbaImage::from_raw(image.width as u32, image.height as u32, image.bytes.into_owned())
+    .ok_or("剪贴板图片数据错误")?;
+  let mut png = Vec::new();
+  image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).map_err(|e| e.to_string())?;
+  Ok(STANDARD.encode(png))
+}
+
+// base64的PNG解码为剪贴板图片
+fn decode_png(png: &str) -> Result<ImageData<'static>, String> {
+  let png = STANDARD.decode(png).map_err(|e| format!("无效的base64: {}", e))?;
+  let image = image::load_from_memory_with_format(&png, ImageFormat::Png)
+    .map_err(|e| format!("读取PNG图片错误: {}", e))?
+    .to_rgba8();
+  Ok(ImageData {
+    width: image.width() as usize,
+    height: image.height() as usize,
+    bytes: Cow::Owned(image.into_raw())
+  })
+}
+
+// 系统剪贴板，不依赖页面中需要权限的navigator.clipboard
+#[derive(Default)]
+pub struct Clipboard {
+  // Linux上剪贴板内容由该对象持有，需要一直保留
+  clipboard: Option<Box<dyn NativeClipboard>>
+}
+
+impl Clipboard {
+  // 使用指定的剪贴板，如模拟模式中的内存实现
+  pub fn with_native(clipboard: Box<dyn NativeClipboard>) -> Self {
+    Self { clipboard: Some(clipboard) }
+  }
+  // 第一次使用时才创建
+  fn clipboard(&mut self) -> Result<&mut dyn NativeClipboard, String> {
+    if self.clipboard.is_none() {
+      self.clipboard = Some(Box::new(ArboardClipboard::new().map_err(|e| e.to_string())?));
+    }
+    Ok(self.clipboard.as_deref_mut().unwrap())
+  }
+  // 读取纯文本
+  pub fn read_text(&mut self) -> Result<Value, String> {
+    get_content(self.clipboard()?.get_text())
+  }
+  // 写入纯文本
+  pub fn write_text(&mut self, text: &str) -> Result<(), String> {
+    self.clipboard()?.set_text(text).map_err(|e| e.to_string())
+  }
+  // 读取HTML
+  pub fn read_html(&mut self) -> Result<Value, String> {
+    get_content(self.clipboard()?.get_html())
+  }
+  // 写入HTML，alt_text为不支持HTML的程序粘贴时使用的纯文本
+  pub fn write_html(&mut self, html: &str, alt_text: Option<&str>) -> Result<(), String> {
+    self.clipboard()?.set_html(html, alt_text).map_err(|e| e.to_string())
+  }
+  // 读取图片，返回base64编码的PNG
+  pub fn read_image(&mut self) -> Result<Value, String> {
+    let image = match self.clipboard()?.get_image() {
+      Ok(image) => image,
+      Err(Error::ContentNotAvailable) => return Ok(Value::Null),
+      Err(e) => return Err(e.to_string())
+    };
+    Ok(Value::String(encode_png(image)?))
+  }
+  // 写入图片，图片为base64编码的PNG
+  pub fn write_image(&mut self, png: &str) -> Result<(), String> {
+    let image = decode_png(png)?;
+    self.clipboard()?.set_image(image).map_err(|e| e.to_string())
+  }
+  // 清空剪贴板
+  pub fn clear(&mut self) -> Result<(), String> {
+    self.clipboard()?.clear().map_err(|e| e.to_string())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+  use crate::listen::to_io_line;
+
+  // 生成难以压缩的噪点图片，使PNG有数MB
+  fn noise_image(width: usize, height: usize) -> ImageData<'static> {
+    let mut seed: u32 = 1;
+    let bytes = (0..width * height * 4).map(|_| {
+      seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+      (seed >> 24) as u8
+    }).collect::<Vec<u8>>();
+    ImageData { width, height, bytes: Cow::Owned(bytes) }
+  }
+
+  #[test]
+  fn large_image_is_sent_as_one_line() {
+    let image = noise_image(1024, 1024);
+    let bytes = image.bytes.clone();
+    let png = encode_png(image).unwrap();
+    assert!(png.len() > 4 * 1024 * 1024);
+    let line = to_io_line(&json!({ "type": "response", "id": "1", "label": "@app", "method": "clipboard_read_image", "data": png }));
+    assert!(!line.contains('\n'));
+    let decoded = decode_png(&png).unwrap();
+    assert_eq!((decoded.width, decoded.height), (1024, 1024));
+    assert_eq!(decoded.bytes, bytes);
+  }
+
+  #[test]
+  fn invalid_png_is_rejected() {
+    assert!(decode_png("不是base64").unwrap_err().starts_with("无效的base64"));
+    assert!(decode_png(&STANDARD.encode("abc")).unwrap_err().starts_with("读取PNG图片错误"));
+  }
+}
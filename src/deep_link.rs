@@ -0,0 +1,110 @@
+use serde_json::Value;
+use crate::event::send_app_event;
+
+// 从启动参数中找出使用已注册协议的url，如 myapp://open?id=1
+pub fn get_urls(args: &[String], schemes: &[String]) -> Vec<String> {
+  args.iter()
+    .filter(|arg| {
+      arg.split_once("://").is_some_and(|(scheme, _)| schemes.iter().any(|s| s.eq_ignore_ascii_case(scheme)))
+    })
+    .cloned()
+    .collect()
+}
+
+// url作为openUrl事件发送给node端
+pub fn send_open_urls(urls: Vec<String>) {
+  for url in urls {
+    send_app_event("openUrl", Some(Value::String(url)));
+  }
+}
+
+// 用户的.desktop文件目录
+#[cfg(target_os = "linux")]
+fn get_applications_dir() -> Result<String, String> {
+  let data_home = std::env::var("XDG_DATA_HOME")
+    .or_else(|_| std::env::var("HOME").map(|home| format!("{}/.local/share", home)))
+    .map_err(|_| "找不到用户数据目录")?;
+  Ok(format!("{}/applications", data_home))
+}
+
+// 协议名只能包含字母、数字和+.-，并以字母开头，协议名会作为.desktop文件名的一部分
+pub fn check_scheme(scheme: &str) -> Result<(), String> {
+  let mut chars = scheme.chars();
+  let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+    && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'));
+  if valid { Ok(()) } else { Err(format!("无效的协议名: {}", scheme)) }
+}
+
+// .desktop文件中的字符串值，换行等控制字符会写入额外的键，反斜杠需要转义
+fn desktop_string(key: &str, value: &str) -> Result<String, String> {
+  if value.chars().any(|c| c.is_control()) {
+    return Err(format!("{}不能包含控制字符", key));
+  }
+  Ok(value.replace('\\', "\\\\"))
+}
+
+// 获取注册协议的参数，返回协议名和转义后的name、exec
+// { scheme, name, exec }
+pub fn get_url_scheme_params(data: &Value) -> Result<(String, String, String), String> {
+  let scheme = data.get("scheme").and_then(|v| v.as_str()).ok_or("必须设置scheme")?;
+  check_scheme(scheme)?;
+  let exec = data.get("exec").and_then(|v| v.as_str()).ok_or("必须设置exec")?;
+  let name = data.get("name").and_then(|v| v.as_str()).unwrap_or(scheme);
+  let name = desktop_string("name", name)?;
+  // Exec中的%是字段代码的前缀，字面的%需要写成%%
+  let exec = desktop_string("exec", &exec.replace('%', "%%"))?;
+  Ok((scheme.to_string(), name, exec))
+}
+
+// 获取注销协议的协议名
+pub fn get_scheme(data: &Value) -> Result<&str, String> {
+  let scheme = data.as_str().ok_or("必须设置scheme")?;
+  check_scheme(scheme)?;
+  Ok(scheme)
+}
+
+// Linux上通过.desktop文件把协议注册给应用，浏览器打开链接时以 exec url 的方式启动应用
+#[cfg(target_os = "linux")]
+pub fn register_url_scheme(data: &Value) -> Result<(), String> {
+  use std::fs;
+  use std::process::Command;
+  let (scheme, name, exec) = get_url_scheme_params(data)?;
+  let dir = get_applications_dir()?;
+  fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+  let file_name = format!("{}-url-handler.desktop", scheme);
+  let desktop = format!(
+    "[Desktop Entry]\nType=Application\nName={}\nExec={} %u\nTerminal=false\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+    name, exec, scheme
+  );
+  fs::write(format!("{}/{}", dir, file_name), desktop).map_err(|e| e.to_string())?;
+  // 设置为该协议的默认程序并刷新MIME缓存
+  let status = Command::new("xdg-mime")
+    .args(["default", &file_name, &format!("x-scheme-handler/{}", scheme)])
+    .status()
+    .map_err(|e| format!("执行xdg-mime错误: {}", e))?;
+  if !status.success() {
+    return Err(format!("执行xdg-mime错误: {}", status));
+  }
+  let _ = Command::new("update-desktop-database").arg(&dir).status();
+  Ok(())
+}
+
+// 删除注册协议时创建的.desktop文件
+#[cfg(target_os = "linux")]
+pub fn unregister_url_scheme(data: &Value) -> Result<(), String> {
+  let scheme = get_scheme(data)?;
+  let dir = get_applications_dir()?;
+  std::fs::remove_file(format!("{}/{}-url-handler.desktop", dir, scheme)).map_err(|e| e.to_string())?;
+  let _ = std::process::Command::new("update-desktop-database").arg(&dir).status();
+  Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn register_url_scheme(_data: &Value) -> Result<(), String> {
+  Err("仅Linux支持注册协议，其他平台需要在安装包中注册".to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn unregister_url_scheme(_data: &Value) -> Result<(), String> {
+  Err("仅Linux支持注册协议，其他平台需要在安装包中注册".to_string())
+}
@@ -0,0 +1,159 @@
+use std::future::Future;
+use std::path::Path;
+use std::thread;
+use serde_json::{Map, Value};
+use rfd::{AsyncFileDialog, AsyncMessageDialog, FileHandle, MessageButtons, MessageDialogResult, MessageLevel};
+use winit::window::Window as WinitWindow;
+use crate::listen::{send_io_message, send_io_error};
+
+// 对话框在子线程中等待结果，不阻塞事件循环，结果直接作为响应发送给node端
+fn respond_async<F>(mut response: Map<String, Value>, future: F)
+where
+  F: Future<Output = Value> + Send + 'static
+{
+  thread::spawn(move || {
+    let data = pollster::block_on(future);
+    response.insert("data".to_string(), data);
+    send_io_message(Value::Object(response));
+  });
+}
+
+fn get_path(file: FileHandle) -> Value {
+  Value::String(file.path().to_string_lossy().to_string())
+}
+
+fn get_paths(files: Option<Vec<FileHandle>>) -> Value {
+  files.map_or(Value::Null, |files| Value::Array(files.into_iter().map(get_path).collect()))
+}
+
+// 创建文件对话框，设置标题、默认路径和过滤器
+// { title, defaultPath, filters: [{ name, extensions }], canCreateDirectories }
+fn get_file_dialog(data: &Value, parent: Option<&WinitWindow>) -> AsyncFileDialog {
+  let mut dialog = AsyncFileDialog::new();
+  if let Some(parent) = parent {
+    dialog = dialog.set_parent(parent);
+  }
+  if let Some(title) = data.get("title").and_then(|v| v.as_str()) {
+    dialog = dialog.set_title(title);
+  }
+  // 默认路径为目录时打开该目录，否则打开所在目录并填入文件名
+  if let Some(default_path) = data.get("defaultPath").and_then(|v| v.as_str()) {
+    let path = Path::new(default_path);
+    if path.is_dir() {
+      dialog = dialog.set_directory(path);
+    }else{
+      if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        dialog = dialog.set_directory(dir);
+      }
+      if let Some(file_name) = path.file_name() {
+        dialog = dialog.set_file_name(file_name.to_string_lossy());
+      }
+    }
+  }
+  if let Some(filters) = data.get("filters").and_then(|v| v.as_array()) {
+    for filter in filters {
+      let name = filter.get("name").and_then(|v| v.as_str()).unwrap_or_default();
+      let extensions: Vec<&str> = filter.get("extensions")
+        .and_then(|v| v.as_array())
+        .map(|exts| exts.iter().filter_map(|ext| ext.as_str()).collect())
+        .unwrap_or_default();
+      dialog = dialog.add_filter(name, &extensions);
+    }
+  }
+  if let Some(can_create) = data.get("canCreateDirectories").and_then(|v| v.as_bool()) {
+    dialog = dialog.set_can_create_directories(can_create);
+  }
+  dialog
+}
+
+// 获取消息对话框的按钮
+pub fn get_message_buttons(value: Option<&Value>) -> Result<MessageButtons, String> {
+  match value {
+    None | Some(Value::Null) => Ok(MessageButtons::Ok),
+    Some(Value::String(buttons)) => match buttons.as_str() {
+      "ok" => Ok(MessageButtons::Ok),
+      "okCancel" => Ok(MessageButtons::OkCancel),
+      "yesNo" => Ok(MessageButtons::YesNo),
+      "yesNoCancel" => Ok(MessageButtons::YesNoCancel),
+      _ => Err(format!("无效的按钮: {}", buttons))
+    },
+    // 自定义按钮文字，最多3个
+    Some(Value::Array(labels)) => {
+      let labels: Vec<String> = labels.iter().filter_map(|v| v.as_str()).map(|v| v.to_string()).collect();
+      match labels.as_slice() {
+        [ok] => Ok(MessageButtons::OkCustom(ok.clone())),
+        [ok, cancel] => Ok(MessageButtons::OkCancelCustom(ok.clone(), cancel.clone())),
+        [yes, no, cancel] => Ok(MessageButtons::YesNoCancelCustom(yes.clone(), no.clone(), cancel.clone())),
+        _ => Err("自定义按钮必须为1-3个".to_string())
+      }
+    },
+    Some(value) => Err(format!("无效的按钮: {}", value))
+  }
+}
+
+fn get_message_result(result: MessageDialogResult) -> Value {
+  Value::String(match result {
+    MessageDialogResult::Yes => "yes".to_string(),
+    MessageDialogResult::No => "no".to_string(),
+    MessageDialogResult::Ok => "ok".to_string(),
+    MessageDialogResult::Cancel => "cancel".to_string(),
+    MessageDialogResult::Custom(label) => label
+  })
+}
+
+// 显示文件或消息对话框，parent为None时对话框不属于任何窗口
+pub fn show_dialog(method: &str, data: &Value, response: Map<String, Value>, parent: Option<&WinitWindow>) {
+  match method {
+    "open_file_dialog" => {
+      let dialog = get_file_dialog(data, parent);
+      if data.get("multiple").and_then(|v| v.as_bool()) == Some(true) {
+        let files = dialog.pick_files();
+        respond_async(response, async move { get_paths(files.await) });
+      }else{
+        let file = dialog.pick_file();
+        respond_async(response, async move { file.await.map_or(Value::Null, get_path) });
+      }
+    },
+    "save_file_dialog" => {
+      let file = get_file_dialog(data, parent).save_file();
+      respond_async(response, async move { file.await.map_or(Value::Null, get_path) });
+    },
+    "pick_folder" => {
+      let dialog = get_file_dialog(data, parent);
+      if data.get("multiple").and_then(|v| v.as_bool()) == Some(true) {
+        let folders = dialog.pick_folders();
+        respond_async(response, async move { get_paths(folders.await) });
+      }else{
+        let folder = dialog.pick_folder();
+        respond_async(response, async move { folder.await.map_or(Value::Null, get_path) });
+      }
+    },
+    "message_dialog" => {
+      let buttons = match get_message_buttons(data.get("buttons")) {
+        Ok(buttons) => buttons,
+        Err(e) => {
+          send_io_error(response, e);
+          return;
+        }
+      };
+      let level = match data.get("level").and_then(|v| v.as_str()) {
+        Some("warning") => MessageLevel::Warning,
+        Some("error") => MessageLevel::Error,
+        _ => MessageLevel::Info
+      };
+      let mut dialog = AsyncMessageDialog::new()
+        .set_level(level)
+        .set_buttons(buttons)
+        .set_description(data.get("message").and_then(|v| v.as_str()).unwrap_or_default());
+      if let Some(title) = data.get("title").and_then(|v| v.as_str()) {
+        dialog = dialog.set_title(title);
+      }
+      if let Some(parent) = parent {
+        dialog = dialog.set_parent(parent);
+      }
+      let result = dialog.show();
+      respond_async(response, async move { get_message_result(result.await) });
+    },
+    _ => {}
+  }
+}
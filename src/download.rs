@@ -0,0 +1,157 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use serde_json::{Map, Value};
+use crate::event::send_window_event;
+
+static NEXT_DOWNLOAD_ID: AtomicU64 = AtomicU64::new(1);
+// node端设置的下载策略，key为窗口label，没有设置的窗口按webview的默认方式下载
+static DOWNLOAD_POLICIES: LazyLock<Mutex<HashMap<String, DownloadPolicy>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+// 下载中的id，key为下载url(下载完成的回调只有url)，同一url的多个下载按开始顺序排列
+static ACTIVE_DOWNLOADS: LazyLock<Mutex<HashMap<String, VecDeque<String>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+// 下载中的保存路径，key为下载id(macOS下载完成时拿不到保存路径)
+static DOWNLOAD_PATHS: LazyLock<Mutex<HashMap<String, PathBuf>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// 窗口的下载策略，开始下载的回调在主线程中执行，不能等待node端答复，需要node端提前设置
+// { action: 'accept' | 'cancel', directory, extensions: { [ext]: 'accept' | 'cancel' } }
+#[derive(Debug, Default, PartialEq)]
+pub struct DownloadPolicy {
+  // 是否默认取消下载
+  cancel: bool,
+  // 保存目录，使用建议的文件名保存
+  directory: Option<PathBuf>,
+  // 按扩展名(小写，不含.)设置是否接受，优先于默认设置
+  extensions: HashMap<String, bool>
+}
+
+// 获取下载的处理方式，true为接受
+fn get_action(value: &Value) -> Result<bool, String> {
+  match value.as_str() {
+    Some("accept") => Ok(true),
+    Some("cancel") => Ok(false),
+    _ => Err(format!("无效的下载处理方式: {}", value))
+  }
+}
+
+// 解析node端设置的下载策略，null表示清除策略
+pub fn get_download_policy(data: &Value) -> Result<Option<DownloadPolicy>, String> {
+  if data.is_null() {
+    return Ok(None);
+  }
+  let attr = data.as_object().ok_or("下载策略必须为对象")?;
+  let mut policy = DownloadPolicy::default();
+  if let Some(action) = attr.get("action").filter(|v| !v.is_null()) {
+    policy.cancel = !get_action(action)?;
+  }
+  if let Some(directory) = attr.get("directory").filter(|v| !v.is_null()) {
+    policy.directory = Some(PathBuf::from(directory.as_str().ok_or("directory必须为字符串")?));
+  }
+  if let Some(extensions) = attr.get("extensions").filter(|v| !v.is_null()) {
+    for (ext, action) in extensions.as_object().ok_or("extensions必须为对象")? {
+      policy.extensions.insert(ext.trim_start_matches('.').to_lowercase(), get_action(action)?);
+    }
+  }
+  Ok(Some(policy))
+}
+
+// 设置窗口的下载策略，None为清除
+pub fn set_download_policy(label: &str, policy: Option<DownloadPolicy>) {
+  let mut policies = DOWNLOAD_POLICIES.lock().unwrap();
+  match policy {
+    Some(policy) => policies.insert(label.to_string(), policy),
+    None => policies.remove(label)
+  };
+}
+
+// 按策略决定下载，返回None表示取消，否则返回保存路径
+fn apply_policy(policy: &DownloadPolicy, filename: &str, path: &Path) -> Option<PathBuf> {
+  let ext = Path::new(filename).extension().map(|ext| ext.to_string_lossy().to_lowercase());
+  let accept = ext.and_then(|ext| policy.extensions.get(&ext).copied()).unwrap_or(!policy.cancel);
+  if !accept {
+    return None;
+  }
+  // 文件名不能跳出保存目录
+  let filename = match filename {
+    "" | "." | ".." => "download",
+    name => name
+  };
+  Some(policy.directory.as_ref().map_or(path.to_path_buf(), |dir| dir.join(filename)))
+}
+
+// 记录开始下载的id和保存路径
+fn start_download(id: &str, url: &str, path: &Path) {
+  ACTIVE_DOWNLOADS.lock().unwrap().entry(url.to_string()).or_default().push_back(id.to_string());
+  DOWNLOAD_PATHS.lock().unwrap().insert(id.to_string(), path.to_path_buf());
+}
+
+// 开始下载，回调在主线程中执行，按窗口的下载策略直接决定是否下载和保存路径
+pub fn handle_download_started(label: &str, url: String, path: &mut PathBuf) -> bool {
+  let id = NEXT_DOWNLOAD_ID.fetch_add(1, Ordering::Relaxed).to_string();
+  let suggested_filename = path.file_name()
+    .map(|name| name.to_string_lossy().to_string())
+    .or_else(|| url.split(['?', '#']).next().and_then(|u| u.rsplit('/').next()).map(|name| name.to_string()))
+    .unwrap_or_default();
+  let dest = match DOWNLOAD_POLICIES.lock().unwrap().get(label) {
+    Some(policy) => apply_policy(policy, &suggested_filename, path),
+    None => Some(path.clone())
+  };
+  let mut data = Map::new();
+  data.insert("id".to_string(), Value::String(id.clone()));
+  data.insert("url".to_string(), Value::String(url.clone()));
+  data.insert("suggestedFilename".to_string(), Value::String(suggested_filename));
+  data.insert("path".to_string(), dest.as_ref().map_or(Value::Null, |p| Value::String(p.to_string_lossy().to_string())));
+  data.insert("accepted".to_string(), Value::Bool(dest.is_some()));
+  send_window_event(label, "downloadStarted", Some(Value::Object(data)));
+  match dest {
+    Some(dest) => {
+      *path = dest;
+      start_download(&id, &url, path);
+      true
+    },
+    None => false
+  }
+}
+
+// 下载完成
+pub fn handle_download_completed(label: &str, url: String, path: Option<PathBuf>, success: bool) {
+  let id = ACTIVE_DOWNLOADS.lock().unwrap().get_mut(&url).and_then(|ids| ids.pop_front());
+  let started_path = id.as_ref().and_then(|id| DOWNLOAD_PATHS.lock().unwrap().remove(id));
+  ACTIVE_DOWNLOADS.lock().unwrap().retain(|_, ids| !ids.is_empty());
+  let path = path.filter(|p| !p.as_os_str().is_empty()).or(started_path);
+  let mut data = Map::new();
+  data.insert("id".to_string(), id.map_or(Value::Null, Value::String));
+  data.insert("url".to_string(), Value::String(url));
+  data.insert("path".to_string(), path.map_or(Value::Null, |p| Value::String(p.to_string_lossy().to_string())));
+  data.insert("success".to_string(), Value::Bool(success));
+  send_window_event(label, "downloadCompleted", Some(Value::Object(data)));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn policy_is_parsed() {
+    assert_eq!(get_download_policy(&Value::Null), Ok(None));
+    let policy = get_download_policy(&json!({ "action": "cancel", "directory": "/tmp", "extensions": { ".PDF": "accept" } })).unwrap().unwrap();
+    assert!(policy.cancel);
+    assert_eq!(policy.directory, Some(PathBuf::from("/tmp")));
+    assert_eq!(policy.extensions.get("pdf"), Some(&true));
+    assert_eq!(get_download_policy(&json!({ "action": "ask" })), Err("无效的下载处理方式: \"ask\"".to_string()));
+    assert_eq!(get_download_policy(&json!({ "directory": 1 })), Err("directory必须为字符串".to_string()));
+  }
+
+  #[test]
+  fn policy_decides_accept_and_path() {
+    let default_path = Path::new("/downloads/report.pdf");
+    let policy = get_download_policy(&json!({ "directory": "/data", "extensions": { "exe": "cancel" } })).unwrap().unwrap();
+    assert_eq!(apply_policy(&policy, "report.pdf", default_path), Some(PathBuf::from("/data/report.pdf")));
+    assert_eq!(apply_policy(&policy, "setup.EXE", default_path), None);
+    assert_eq!(apply_policy(&policy, "..", default_path), Some(PathBuf::from("/data/download")));
+    let policy = get_download_policy(&json!({ "action": "cancel", "extensions": { "pdf": "accept" } })).unwrap().unwrap();
+    assert_eq!(apply_policy(&policy, "report.pdf", default_path), Some(default_path.to_path_buf()));
+    assert_eq!(apply_policy(&policy, "image.png", default_path), None);
+  }
+}
@@ -1,14 +1,37 @@
 use crate::application::Application;
-use winit::event_loop::ActiveEventLoop;
 use winit::window::WindowId;
-use winit::event::WindowEvent;
+use winit::event::{Ime, WindowEvent};
 use winit::dpi::Size;
 use serde_json::{Map, Value};
 use crate::listen::send_io_message;
+use crate::platform::Platform;
+
+// 发送窗口事件
+pub fn send_window_event(label: &str, method: &str, data: Option<Value>) {
+  let mut response = Map::new();
+  response.insert("type".to_string(), Value::String("windowEvent".to_string()));
+  response.insert("label".to_string(), Value::String(label.to_string()));
+  response.insert("method".to_string(), Value::String(method.to_string()));
+  if let Some(data) = data {
+    response.insert("data".to_string(), data);
+  }
+  send_io_message(Value::Object(response));
+}
+
+// 发送不属于任何窗口的应用事件
+pub fn send_app_event(method: &str, data: Option<Value>) {
+  let mut response = Map::new();
+  response.insert("type".to_string(), Value::String("appEvent".to_string()));
+  response.insert("method".to_string(), Value::String(method.to_string()));
+  if let Some(data) = data {
+    response.insert("data".to_string(), data);
+  }
+  send_io_message(Value::Object(response));
+}
 
 pub fn handle_window_event(
   app:&mut Application,
-  event_loop:&ActiveEventLoop,
+  platform:&dyn Platform,
   window_id: WindowId,
   event: WindowEvent
 ) {
@@ -16,7 +39,10 @@ pub fn handle_window_event(
   let len = app.windows.len();
   let windows_clone = app.windows.clone();
   let mut windows = windows_clone.values();
-  let window = windows.find(|w| w.id() == window_id).unwrap();
+  // 窗口关闭后仍可能收到该窗口的事件(如Destroyed)
+  let Some(window) = windows.find(|w| w.id() == window_id) else {
+    return;
+  };
 
   let mut response = serde_json::Map::new();
   response.insert("type".to_string(), Value::String("windowEvent".to_string()));
@@ -28,13 +54,19 @@ pub fn handle_window_event(
       send_io_message(Value::Object(response));
       // 如果只有一个窗口，直接退出进程
       if len == 1 {
-        event_loop.exit();
+        window.save_state();
+        platform.exit();
         return;
       }
       // 关闭窗口
       app.close_window(window.label.clone()); 
+      // 子窗口随父窗口一起关闭后可能已经没有窗口了
+      if app.windows.is_empty() {
+        platform.exit();
+      }
     },
     WindowEvent::Moved(position) => {
+      window.update_state();
       response.insert("method".to_string(), Value::String("move".to_string()));
       let mut obj = Map::new();
       obj.insert("x".to_string(), position.x.into());
@@ -48,6 +80,10 @@ pub fn handle_window_event(
     },
     WindowEvent::Focused(focused) => {
       if focused {
+        // 存在模态子窗口时焦点转移到子窗口
+        if let Some(child) = app.modal_child(&window.label) {
+          child.focus_window();
+        }
         response.insert("method".to_string(), Value::String("focus".to_string()));
       }else {
         response.insert("method".to_string(), Value::String("blur".to_string()));
@@ -89,6 +125,7 @@ pub fn handle_window_event(
     },
     WindowEvent::Resized(size) => {
       window.resize(Size::Physical(size));
+      window.update_state();
       response.insert("method".to_string(), Value::String("resize".to_string()));
       let mut data = Map::new();
       data.insert("width".to_string(), size.width.into());
@@ -96,6 +133,36 @@ pub fn handle_window_event(
       response.insert("data".to_string(), Value::Object(data));
       send_io_message(Value::Object(response));
     },
+    WindowEvent::RedrawRequested => {
+      response.insert("method".to_string(), Value::String("redrawRequested".to_string()));
+      send_io_message(Value::Object(response));
+    },
+    // 输入法事件
+    WindowEvent::Ime(ime) => {
+      match ime {
+        Ime::Enabled => {
+          response.insert("method".to_string(), Value::String("imeEnabled".to_string()));
+        },
+        Ime::Preedit(text, cursor) => {
+          response.insert("method".to_string(), Value::String("imePreedit".to_string()));
+          let mut data = Map::new();
+          data.insert("text".to_string(), Value::String(text));
+          // 光标位置为预编辑文本中的字节偏移，不显示光标时为null
+          let (start, end) = cursor.map_or((Value::Null, Value::Null), |(start, end)| (start.into(), end.into()));
+          data.insert("cursorStart".to_string(), start);
+          data.insert("cursorEnd".to_string(), end);
+          response.insert("data".to_string(), Value::Object(data));
+        },
+        Ime::Commit(text) => {
+          response.insert("method".to_string(), Value::String("imeCommit".to_string()));
+          response.insert("data".to_string(), Value::String(text));
+        },
+        Ime::Disabled => {
+          response.insert("method".to_string(), Value::String("imeDisabled".to_string()));
+        }
+      }
+      send_io_message(Value::Object(response));
+    },
     _ => (),
   }
 }
\ No newline at end of file
@@ -0,0 +1,131 @@
+use serde_json::Value;
+use winit::dpi::{LogicalPosition, PhysicalPosition};
+use wry::InitializationScript;
+use crate::window::Window;
+
+// 注入页面的脚本，页面通过window.nodeWinitWry设置鼠标穿透
+// 传入的区域可以是元素或{x, y, width, height}形式的矩形(相对于webview的css像素)
+const HITTEST_SCRIPT: &str = r#"
+(function () {
+  var post = function (message) { window.ipc.postMessage(JSON.stringify(message)); };
+  var toRect = function (region) {
+    if (region && typeof region.getBoundingClientRect === 'function') {
+      var rect = region.getBoundingClientRect();
+      return { x: rect.left, y: rect.top, width: rect.width, height: rect.height };
+    }
+    return region;
+  };
+  window.nodeWinitWry = Object.assign(window.nodeWinitWry || {}, {
+    setCursorHittest: function (hittest) {
+      post({ type: 'setCursorHittest', hittest: !!hittest });
+    },
+    setHittestRegions: function (regions) {
+      if (!__REGIONS_SUPPORTED__) throw new Error('当前平台不支持设置可点击区域，请使用setCursorHittest');
+      post({ type: 'setHittestRegions', regions: regions ? Array.prototype.map.call(regions, toRect) : null });
+    }
+  });
+})();
+"#;
+
+// 只有Windows能在鼠标穿透时查询光标位置，其他平台不支持可点击区域
+pub const HITTEST_REGIONS_SUPPORTED: bool = cfg!(target_os = "windows");
+
+pub fn hittest_script() -> InitializationScript {
+  InitializationScript {
+    script: HITTEST_SCRIPT.replace("__REGIONS_SUPPORTED__", &HITTEST_REGIONS_SUPPORTED.to_string()),
+    for_main_frame_only: true
+  }
+}
+
+// 可以接收鼠标事件的区域(相对于主webview的逻辑像素)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HittestRegion {
+  pub x: f64,
+  pub y: f64,
+  pub width: f64,
+  pub height: f64
+}
+
+impl HittestRegion {
+  pub fn from_value(value: &Value) -> Option<Self> {
+    let obj = value.as_object()?;
+    Some(Self {
+      x: obj.get("x")?.as_f64()?,
+      y: obj.get("y")?.as_f64()?,
+      width: obj.get("width")?.as_f64()?,
+      height: obj.get("height")?.as_f64()?
+    })
+  }
+  pub fn contains(&self, position: LogicalPosition<f64>) -> bool {
+    position.x >= self.x && position.x < self.x + self.width
+      && position.y >= self.y && position.y < self.y + self.height
+  }
+}
+
+// 获取光标在屏幕上的物理坐标，窗口鼠标穿透时收不到光标事件，只能主动查询
+#[cfg(target_os = "windows")]
+fn cursor_screen_position() -> Option<PhysicalPosition<f64>> {
+  use windows::Win32::Foundation::POINT;
+  use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+  let mut point = POINT::default();
+  unsafe { GetCursorPos(&mut point) }.ok()?;
+  Some(PhysicalPosition::new(point.x as f64, point.y as f64))
+}
+#[cfg(not(target_os = "windows"))]
+fn cursor_screen_position() -> Option<PhysicalPosition<f64>> {
+  None
+}
+
+// 处理页面发送的鼠标穿透消息，返回false表示不是鼠标穿透消息
+pub fn handle_hittest_message(window: &mut Window, message: &Value) -> bool {
+  match message.get("type").and_then(|v| v.as_str()) {
+    Some("setCursorHittest") => {
+      let hittest = message.get("hittest").and_then(|v| v.as_bool()).unwrap_or(true);
+      window.hittest_regions = None;
+      let _ = window.set_cursor_hittest(hittest);
+      true
+    },
+    Some("setHittestRegions") => {
+      match message.get("regions").and_then(|v| v.as_array()) {
+        // 页面脚本已经抛出错误，这里忽略
+        Some(_) if !HITTEST_REGIONS_SUPPORTED => {},
+        Some(regions) => {
+          window.hittest_regions = Some(regions.iter().filter_map(HittestRegion::from_value).collect());
+          // 查询不到光标位置时不启用区域穿透
+          if !update_region_hittest(window) {
+            window.hittest_regions = None;
+            let _ = window.set_cursor_hittest(true);
+          }
+        },
+        None => {
+          window.hittest_regions = None;
+          let _ = window.set_cursor_hittest(true);
+        }
+      }
+      true
+    },
+    _ => false
+  }
+}
+
+// 根据光标是否位于可点击区域内切换鼠标穿透，返回false表示当前平台无法查询光标位置
+pub fn update_region_hittest(window: &mut Window) -> bool {
+  let Some(regions) = &window.hittest_regions else {
+    return true;
+  };
+  let Some(cursor) = cursor_screen_position() else {
+    return false;
+  };
+  let Ok(origin) = window.inner_position() else {
+    return false;
+  };
+  let scale_factor = window.scale_factor();
+  let (webview_position, _) = window.layout.resolve(window.logical_size());
+  let position = PhysicalPosition::new(cursor.x - origin.x as f64, cursor.y - origin.y as f64).to_logical::<f64>(scale_factor);
+  let position = LogicalPosition::new(position.x - webview_position.x, position.y - webview_position.y);
+  let hittest = regions.iter().any(|region| region.contains(position));
+  if hittest != window.cursor_hittest {
+    let _ = window.set_cursor_hittest(hittest);
+  }
+  true
+}
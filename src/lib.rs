@@ -1,4 +1,21 @@
 pub mod window;
 pub mod application;
 pub mod listen;
-pub mod event;
\ No newline at end of file
+pub mod event;
+pub mod download;
+pub mod webview;
+pub mod state;
+pub mod hittest;
+pub mod app_listen;
+pub mod menu;
+pub mod tray;
+pub mod shortcut;
+pub mod dialog;
+pub mod clipboard;
+pub mod notification;
+pub mod single_instance;
+pub mod deep_link;
+pub mod mock;
+pub mod script;
+pub mod automation;
+pub mod platform;
\ No newline at end of file
@@ -1,13 +1,27 @@
 use serde_json::{Map, Number, Value};
 use winit::dpi::{ LogicalPosition, Size, LogicalSize };
-use winit::event_loop::ActiveEventLoop;
-use winit::window::{ WindowButtons, Fullscreen, WindowLevel, UserAttentionType, Theme, ResizeDirection, WindowAttributes };
+use winit::window::{ CursorIcon, CursorGrabMode, CustomCursor, ImePurpose, WindowButtons, Fullscreen, WindowLevel, UserAttentionType, Theme, ResizeDirection, WindowAttributes };
 use winit::monitor::MonitorHandle;
 use std::io::{self, Write};
+#[cfg(test)]
+use std::cell::RefCell;
+use std::collections::HashMap;
 use crate::application::Application;
-use std::path::Path;
+use crate::webview::{Layout, Length};
+use crate::state::WindowState;
+use crate::window::load_rgba_image;
+use crate::hittest::hittest_script;
+use crate::app_listen::{APP_LABEL, handle_app_listen};
+use crate::menu::build_menu;
+use crate::automation::handle_automation;
+use crate::download::{get_download_policy, set_download_policy};
+use crate::script::evaluate;
+use crate::platform::Platform;
+use std::path::{Path, PathBuf};
 use image::GenericImageView;
-use wry::WebViewAttributes;
+use wry::{WebViewAttributes, ProxyConfig, ProxyEndpoint};
+use wry::http::{HeaderMap, HeaderName, HeaderValue};
+use wry::cookie::{Cookie, SameSite, time::OffsetDateTime};
 
 pub const IO_CHANNEL_PREFIX: &str = "_ioc:";
 
@@ -24,15 +38,246 @@ fn get_monitor_info(monitor:MonitorHandle) -> Value {
   Value::Object(data)
 }
 
+#[cfg(test)]
+thread_local! {
+  // 测试时记录当前线程发送的IO消息，不输出到stdout
+  static IO_RECORDER: RefCell<Option<Vec<Value>>> = const { RefCell::new(None) };
+}
+
+// 开始记录当前线程发送的IO消息
+#[cfg(test)]
+pub fn record_io_messages() {
+  IO_RECORDER.with(|recorder| *recorder.borrow_mut() = Some(Vec::new()));
+}
+
+// 取出已记录的IO消息
+#[cfg(test)]
+pub fn take_io_messages() -> Vec<Value> {
+  IO_RECORDER.with(|recorder| recorder.borrow_mut().as_mut().map(std::mem::take).unwrap_or_default())
+}
+
+// 创建响应消息
+pub fn create_response(id: &str, label: &str, method: &str) -> Map<String, Value> {
+  let mut response = Map::new();
+  response.insert("id".to_string(), Value::String(id.to_string()));
+  response.insert("label".to_string(), Value::String(label.to_string()));
+  response.insert("method".to_string(), Value::String(method.to_string()));
+  response.insert("type".to_string(), Value::String("response".to_string()));
+  response
+}
+
 // 发送IO消息
 pub fn send_io_message(msg: Value) {
-  let json_str = serde_json::to_string(&msg).unwrap();
+  // 测试中正在记录时不输出到stdout
+  #[cfg(test)]
+  let Some(msg) = IO_RECORDER.with(|recorder| match recorder.borrow_mut().as_mut() {
+    Some(messages) => {
+      messages.push(msg);
+      None
+    },
+    None => Some(msg)
+  }) else {
+    return;
+  };
   // 创建一个输出流
   let mut output = io::stdout();
-  writeln!(output, "{}{}", IO_CHANNEL_PREFIX, json_str).unwrap();
+  writeln!(output, "{}", to_io_line(&msg)).unwrap();
   output.flush().unwrap();
 }
 
+// 序列化为一行IO消息，字符串中的换行会被转义，node端按行读取
+pub fn to_io_line(msg: &Value) -> String {
+  format!("{}{}", IO_CHANNEL_PREFIX, serde_json::to_string(msg).unwrap())
+}
+
+// 发送错误消息
+pub fn send_io_error(mut response: Map<String, Value>, error: String) {
+  response.insert("error".to_string(), Value::String(error));
+  send_io_message(Value::Object(response));
+}
+
+// 获取cookie信息
+pub fn get_cookie_info(cookie:&Cookie) -> Value {
+  let mut data = Map::new();
+  data.insert("name".to_string(), Value::String(cookie.name().to_string()));
+  data.insert("value".to_string(), Value::String(cookie.value().to_string()));
+  data.insert("domain".to_string(), cookie.domain().map_or(Value::Null, |d| Value::String(d.to_string())));
+  data.insert("path".to_string(), cookie.path().map_or(Value::Null, |p| Value::String(p.to_string())));
+  // 过期时间为unix时间戳(秒)，会话cookie为null
+  data.insert("expires".to_string(), cookie.expires_datetime().map_or(Value::Null, |t| t.unix_timestamp().into()));
+  data.insert("secure".to_string(), Value::Bool(cookie.secure().unwrap_or(false)));
+  data.insert("httpOnly".to_string(), Value::Bool(cookie.http_only().unwrap_or(false)));
+  let same_site = match cookie.same_site() {
+    Some(SameSite::Strict) => Value::String("strict".to_string()),
+    Some(SameSite::Lax) => Value::String("lax".to_string()),
+    Some(SameSite::None) => Value::String("none".to_string()),
+    None => Value::Null
+  };
+  data.insert("sameSite".to_string(), same_site);
+  Value::Object(data)
+}
+
+// 获取Cookie实例
+pub fn get_cookie(obj:&Value) -> Result<Cookie<'static>, String> {
+  let attr = obj.as_object().ok_or("cookie必须为对象")?;
+  let name = attr.get("name").and_then(|v| v.as_str()).ok_or("cookie缺少name")?;
+  let value = attr.get("value").and_then(|v| v.as_str()).unwrap_or("");
+  let mut cookie = Cookie::new(name.to_string(), value.to_string());
+  if let Some(domain) = attr.get("domain").and_then(|v| v.as_str()) {
+    cookie.set_domain(domain.to_string());
+  }
+  if let Some(path) = attr.get("path").and_then(|v| v.as_str()) {
+    cookie.set_path(path.to_string());
+  }
+  if let Some(expires) = attr.get("expires").and_then(|v| v.as_i64()) {
+    let datetime = OffsetDateTime::from_unix_timestamp(expires).map_err(|e| e.to_string())?;
+    cookie.set_expires(datetime);
+  }
+  if let Some(secure) = attr.get("secure").and_then(|v| v.as_bool()) {
+    cookie.set_secure(secure);
+  }
+  if let Some(http_only) = attr.get("httpOnly").and_then(|v| v.as_bool()) {
+    cookie.set_http_only(http_only);
+  }
+  if let Some(same_site) = attr.get("sameSite").and_then(|v| v.as_str()) {
+    match same_site {
+      "strict" => cookie.set_same_site(SameSite::Strict),
+      "lax" => cookie.set_same_site(SameSite::Lax),
+      "none" => cookie.set_same_site(SameSite::None),
+      _ => return Err(format!("不支持的sameSite值: {}", same_site))
+    }
+  }
+  Ok(cookie)
+}
+
+// 获取请求头
+pub fn get_headers(obj:&Value) -> Result<HeaderMap, String> {
+  let attr = obj.as_object().ok_or("headers必须为对象")?;
+  let mut headers = HeaderMap::new();
+  for (key, value) in attr {
+    let name = HeaderName::from_bytes(key.as_bytes()).map_err(|e| e.to_string())?;
+    let value = value.as_str().ok_or(format!("请求头 {} 的值必须为字符串", key))?;
+    let value = HeaderValue::from_str(value).map_err(|e| e.to_string())?;
+    headers.insert(name, value);
+  }
+  Ok(headers)
+}
+
+// 获取代理配置
+fn get_proxy_config(obj:&Value) -> Result<ProxyConfig, String> {
+  let attr = obj.as_object().ok_or("proxy必须为对象")?;
+  let host = attr.get("host").and_then(|v| v.as_str()).ok_or("代理必须设置host")?.to_string();
+  let port = attr.get("port")
+    .and_then(|v| v.as_u64())
+    .filter(|port| *port <= u16::MAX as u64)
+    .ok_or("代理的port必须为0-65535的整数")?
+    .to_string();
+  let endpoint = ProxyEndpoint { host, port };
+  match attr.get("type").and_then(|v| v.as_str()) {
+    Some("http") => Ok(ProxyConfig::Http(endpoint)),
+    Some("socks5") => Ok(ProxyConfig::Socks5(endpoint)),
+    _ => Err(format!("无效的代理类型: {}", attr.get("type").unwrap_or(&Value::Null)))
+  }
+}
+
+// 获取webview相关属性
+pub fn get_webview_attributes(data:&Map<String, Value>) -> Result<(WebViewAttributes<'static>, Option<PathBuf>), String> {
+  let mut webview_attr = WebViewAttributes::default();
+  let mut data_directory: Option<PathBuf> = None;
+  for key in data.keys() {
+    match key.as_str() {
+      "url" => {
+        if let Some(url) = data.get("url").unwrap().as_str() {
+          webview_attr.url = Some(url.to_string());
+        }
+      },
+      "backgroundColor" => {
+        if let Some(color) = data.get("backgroundColor").unwrap().as_array() {
+          let colors: Result<Vec<u8>, &str> = (0..4)
+            .map(|i| {
+              color.get(i)
+              .and_then(|v| v.as_u64())
+              .and_then(|v| Some(v as u8))
+              .ok_or("颜色值必须为0-255的整数")
+            })
+            .collect();
+          match colors {
+            Ok(values) => {
+              let colors = (values[0] as u8, values[1] as u8, values[2] as u8, values[3] as u8);
+              webview_attr.background_color = Some(colors);
+            }
+            Err(e) => {
+              println!("错误: {}", e);
+            }
+          }
+        }
+      },
+      "html" => {
+        if let Some(html) = data.get("html").unwrap().as_str() {
+          webview_attr.html = Some(html.to_string());
+        }
+      },
+      "devtools" => {
+        if let Some(devtools) = data.get("devtools").unwrap().as_bool() {
+          webview_attr.devtools = devtools;
+        }
+      },
+      "autoplay" => {
+        if let Some(autoplay) = data.get("autoplay").unwrap().as_bool() {
+          webview_attr.autoplay = autoplay;
+        }
+      },
+      "userAgent" => {
+        if let Some(user_agent) = data.get("userAgent").unwrap().as_str() {
+          webview_attr.user_agent = Some(user_agent.to_string());
+        }
+      },
+      "headers" => {
+        let headers = data.get("headers").unwrap();
+        if !headers.is_null() {
+          webview_attr.headers = Some(get_headers(headers)?);
+        }
+      },
+      "proxy" => {
+        let proxy = data.get("proxy").unwrap();
+        if !proxy.is_null() {
+          webview_attr.proxy_config = Some(get_proxy_config(proxy)?);
+        }
+      },
+      "dataDirectory" => {
+        if let Some(dir) = data.get("dataDirectory").unwrap().as_str() {
+          data_directory = Some(PathBuf::from(dir));
+        }
+      },
+      "incognito" => {
+        if let Some(incognito) = data.get("incognito").unwrap().as_bool() {
+          webview_attr.incognito = incognito;
+        }
+      },
+      "transparent" => {
+        if let Some(transparent) = data.get("transparent").unwrap().as_bool() {
+          webview_attr.transparent = transparent;
+        }
+      },
+      _ => {}
+    }
+  }
+  Ok((webview_attr, data_directory))
+}
+
+// 获取webview内边距对应的布局，用于给原生区域预留空间
+fn get_inset(obj:&Value) -> Result<Layout, String> {
+  let attr = obj.as_object().ok_or("webviewInset必须为对象")?;
+  let get = |key: &str| Length::from_value(attr.get(key).unwrap_or(&Value::Null));
+  Ok(Layout {
+    x: get("left")?,
+    y: get("top")?,
+    right: get("right")?,
+    bottom: get("bottom")?,
+    ..Layout::default()
+  })
+}
+
 // 获取Size实例
 fn get_size(obj:&Value) -> Option<Size> {
   if let Some(attr) = obj.as_object() {
@@ -54,8 +299,48 @@ fn get_position(obj:&Value) -> Option<LogicalPosition<i32>>{
     None
   }
 }
+// 获取坐标，x和y可以为小数，缺少或类型错误时返回错误
+pub fn parse_position(obj:&Value) -> Result<LogicalPosition<f64>, String> {
+  let get = |key: &str| obj.get(key).and_then(|v| v.as_f64()).ok_or(format!("坐标的{}必须为数字", key));
+  Ok(LogicalPosition::new(get("x")?, get("y")?))
+}
+// 获取尺寸，缺少或类型错误时返回错误
+pub fn parse_size(obj:&Value) -> Result<LogicalSize<f64>, String> {
+  let get = |key: &str| obj.get(key).and_then(|v| v.as_f64()).ok_or(format!("尺寸的{}必须为数字", key));
+  Ok(LogicalSize::new(get("width")?, get("height")?))
+}
+// 光标图片的尺寸和热点坐标为u16，超出时返回错误
+fn to_cursor_length(name: &str, value: u64) -> Result<u16, String> {
+  u16::try_from(value).map_err(|_| format!("光标的{}不能超过{}", name, u16::MAX))
+}
+// 获取自定义光标的图片路径和热点坐标 { path, hotspotX, hotspotY }
+fn get_custom_cursor_params(obj:&Value) -> Result<(String, u16, u16), String> {
+  let path = obj.get("path").and_then(|v| v.as_str()).ok_or("必须设置光标图片path")?;
+  let hotspot = |key: &str, name: &str| match obj.get(key).filter(|v| !v.is_null()) {
+    None => Ok(0),
+    Some(value) => to_cursor_length(name, value.as_u64().ok_or(format!("光标的{}必须为非负整数", name))?)
+  };
+  Ok((path.to_string(), hotspot("hotspotX", "热点x坐标")?, hotspot("hotspotY", "热点y坐标")?))
+}
+// 获取光标锁定模式
+fn get_cursor_grab_mode(mode: &str) -> Result<CursorGrabMode, String> {
+  match mode {
+    "none" => Ok(CursorGrabMode::None),
+    "confined" => Ok(CursorGrabMode::Confined),
+    "locked" => Ok(CursorGrabMode::Locked),
+    _ => Err(format!("无效的光标锁定模式: {}", mode))
+  }
+}
+// 获取输入法候选框区域 { position, size }
+pub fn get_ime_cursor_area(obj:&Value) -> Result<(LogicalPosition<f64>, LogicalSize<f64>), String> {
+  match (obj.get("position"), obj.get("size")) {
+    (Some(position), Some(size)) => Ok((parse_position(position)?, parse_size(size)?)),
+    _ => Err("输入法区域必须包含position和size".to_string())
+  }
+}
+
 // 处理IO收到的信息
-pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoop) {
+pub fn handle_listen(app:&mut Application, str:&str, platform: &dyn Platform) {
   let message:Value = serde_json::from_str(str).unwrap();
   let id = message.get("id").unwrap().as_str().unwrap();
   let label = message.get("label").unwrap().as_str().unwrap();
@@ -63,12 +348,12 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
   let data = message.get("data").unwrap_or(&Value::Null);
   let window = app.get_window(label.to_string());
   // 返回消息
-  let mut response = serde_json::Map::new();
-  response.insert("id".to_string(), Value::String(id.to_string()));
-  response.insert("label".to_string(), Value::String(label.to_string()));
-  response.insert("method".to_string(), Value::String(method.to_string()));
-  response.insert("type".to_string(), Value::String("response".to_string()));
+  let mut response = create_response(id, label, method);
 
+  if label == APP_LABEL {
+    handle_app_listen(app, method, data, response, platform);
+    return;
+  }
   match method {
     "create" => {
       if let Some(_) = window {
@@ -77,50 +362,90 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
       }
       // 创建窗口
       let mut window_attr = WindowAttributes::default();
-      let mut webview_attr = WebViewAttributes::default();
+      let webview_attributes = match data.as_object() {
+        Some(data) => get_webview_attributes(data),
+        None => Ok((WebViewAttributes::default(), None))
+      };
+      let (webview_attr, data_directory) = match webview_attributes {
+        Ok(attributes) => attributes,
+        Err(e) => {
+          send_io_error(response, e);
+          return;
+        }
+      };
+      let mut layout = Layout::fill();
+      let mut parent: Option<String> = None;
+      let mut modal = false;
+      let mut state_file: Option<PathBuf> = None;
+      let mut restore_state = true;
+      let mut cursor_hittest = true;
+      let mut hittest_regions = false;
+      let mut download_policy = None;
       if let Some(data) = data.as_object() {
         for key in data.keys() {
           match key.as_str() {
-            // webview相关属性
-            "url" => {
-              if let Some(url) = data.get("url").unwrap().as_str() {
-                webview_attr.url = Some(url.to_string());
+            "parent" => {
+              if let Some(p) = data.get("parent").unwrap().as_str() {
+                match app.get_window(p.to_string()) {
+                  Some(parent_window) => {
+                    window_attr = parent_window.owned_window_attributes(window_attr);
+                    parent = Some(p.to_string());
+                  },
+                  None => {
+                    send_io_error(response, format!("父窗口 {} 不存在", p));
+                    return;
+                  }
+                }
               }
             },
-            "backgroundColor" => {
-              if let Some(color) = data.get("backgroundColor").unwrap().as_array() {
-                let colors: Result<Vec<u8>, &str> = (0..4)
-                  .map(|i| {
-                    color.get(i)
-                    .and_then(|v| v.as_u64())
-                    .and_then(|v| Some(v as u8))
-                    .ok_or("颜色值必须为0-255的整数")
-                  })
-                  .collect();
-                match colors {
-                  Ok(values) => {
-                    let colors = (values[0] as u8, values[1] as u8, values[2] as u8, values[3] as u8);
-                    webview_attr.background_color = Some(colors);
-                  }
-                  Err(e) => {
-                    println!("错误: {}", e);
-                  }
+            "modal" => {
+              if let Some(m) = data.get("modal").unwrap().as_bool() {
+                // 只有Windows能禁用父窗口的输入
+                if m && !cfg!(target_os = "windows") {
+                  send_io_error(response, "模态窗口仅支持Windows".to_string());
+                  return;
                 }
+                modal = m;
+              }
+            },
+            "stateFile" => {
+              if let Some(file) = data.get("stateFile").unwrap().as_str() {
+                state_file = Some(PathBuf::from(file));
               }
             },
-            "html" => {
-              if let Some(html) = data.get("html").unwrap().as_str() {
-                webview_attr.html = Some(html.to_string());
+            "restoreState" => {
+              if let Some(restore) = data.get("restoreState").unwrap().as_bool() {
+                restore_state = restore;
               }
             },
-            "devtools" => {
-              if let Some(devtools) = data.get("devtools").unwrap().as_bool() {
-                webview_attr.devtools = devtools;
+            "cursorHittest" => {
+              if let Some(hittest) = data.get("cursorHittest").unwrap().as_bool() {
+                cursor_hittest = hittest;
               }
             },
-            "autoplay" => {
-              if let Some(autoplay) = data.get("autoplay").unwrap().as_bool() {
-                webview_attr.autoplay = autoplay;
+            "hittestRegions" => {
+              if let Some(enabled) = data.get("hittestRegions").unwrap().as_bool() {
+                hittest_regions = enabled;
+              }
+            },
+            "downloadPolicy" => {
+              match get_download_policy(data.get("downloadPolicy").unwrap()) {
+                Ok(policy) => download_policy = policy,
+                Err(e) => {
+                  send_io_error(response, e);
+                  return;
+                }
+              }
+            },
+            "webviewInset" => {
+              match get_inset(data.get("webviewInset").unwrap()) {
+                Ok(inset) => {
+                  layout = inset;
+                }
+                Err(e) => {
+                  send_io_error(response, e);
+                  return;
+                }
               }
             },
             "innerSize" => {
@@ -187,7 +512,6 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
             "transparent" => {
               if let Some(transparent) = data.get("transparent").unwrap().as_bool() {
                 window_attr = window_attr.with_transparent(transparent);
-                webview_attr.transparent = transparent;
               }
             },
             "blur" => {
@@ -270,7 +594,7 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
               let full = data.get("fullscreen").unwrap();
               if full.is_number() {
                 let monitor_name = String::from("Monitor #") + full.as_number().unwrap().to_string().as_str();
-                let monitors = event_loop.available_monitors();
+                let monitors = platform.available_monitors();
                 let mut has = false;
                 for m in monitors {
                   let name = m.clone().name().unwrap();
@@ -291,7 +615,53 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
           }
         }
       }
-      let window_id = app.create_new_window(event_loop, label.to_string(), window_attr, webview_attr);
+      // 允许页面通过ipc设置鼠标穿透和可点击区域
+      let mut webview_attr = webview_attr;
+      if hittest_regions {
+        webview_attr.initialization_scripts.push(hittest_script());
+      }
+      // 还原上次保存的窗口状态
+      if let (Some(file), true) = (&state_file, restore_state) {
+        if let Some(state) = WindowState::load(file) {
+          window_attr = state.apply(window_attr, platform);
+        }
+      }
+      // 窗口菜单栏，未设置时使用应用菜单，菜单无效时不创建窗口
+      let menu = match data.get("menu").filter(|items| !items.is_null()).map(|items| build_menu(items, &mut app.menu_items)) {
+        Some(Ok(menu)) => Some(menu),
+        Some(Err(e)) => {
+          send_io_error(response, e);
+          return;
+        },
+        None => None
+      };
+      let window_id = match app.create_new_window(platform, label.to_string(), window_attr, webview_attr, data_directory, layout) {
+        Ok(window_id) => window_id,
+        Err(e) => {
+          app.retain_menu_items();
+          send_io_error(response, e);
+          return;
+        }
+      };
+      if let Some(parent) = parent {
+        app.set_window_parent(label, parent, modal);
+      }
+      set_download_policy(label, download_policy);
+      if let Some(window) = app.windows.get_mut(label) {
+        window.set_state_file(state_file);
+        window.page_hittest = hittest_regions;
+        if !cursor_hittest {
+          if let Err(e) = window.set_cursor_hittest(false) {
+            println!("设置鼠标穿透错误: {}", e);
+          }
+        }
+      }
+      // 菜单设置失败时关闭窗口并返回错误
+      if let Err(e) = app.set_window_menu(label, menu) {
+        app.close_window(label.to_string());
+        send_io_error(response, e);
+        return;
+      }
       let id:u64 = window_id.into();
       response.insert("data".to_string(), Value::String(id.to_string()));
       send_io_message(Value::Object(response));
@@ -305,6 +675,19 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
         }
       }
     },
+    "load_url_with_headers" => {
+      if let Some(window) = window {
+        let url = data.get("url").and_then(|v| v.as_str());
+        match (url, get_headers(data.get("headers").unwrap_or(&Value::Null))) {
+          (Some(url), Ok(headers)) => {
+            window.load_url_with_headers(url.to_string(), headers);
+            send_io_message(Value::Object(response));
+          },
+          (None, _) => send_io_error(response, "缺少url".to_string()),
+          (_, Err(e)) => send_io_error(response, e)
+        }
+      }
+    },
     "url" => {
       if let Some(window) = window {
         let url = window.url();
@@ -325,33 +708,51 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
       if data.is_string() {
         let script = data.as_str().unwrap();
         if let Some(window) = window {
-          window.evaluate_script_with_callback(script, move |str|{
-            let mut res = response.clone();
+          let callback_response = response.clone();
+          let result = window.evaluate_script_with_callback(script, move |str|{
+            let mut res = callback_response.clone();
             res.insert("data".to_string(), Value::String(str));
             send_io_message(Value::Object(res));
           });
+          if let Err(e) = result {
+            send_io_error(response, e.to_string());
+          }
+        }
+      }
+    },
+    // 等待promise完成并返回json结果，异常时返回错误信息和调用栈
+    "evaluate" => {
+      if let Some(window) = window {
+        evaluate(window, data, response);
+      }
+    },
+    "open_devtools" => {
+      if let Some(window) = window {
+        match window.open_devtools() {
+          Ok(_) => send_io_message(Value::Object(response)),
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    "close_devtools" => {
+      if let Some(window) = window {
+        match window.close_devtools() {
+          Ok(_) => send_io_message(Value::Object(response)),
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    "is_devtools_open" => {
+      if let Some(window) = window {
+        match window.is_devtools_open() {
+          Ok(open) => {
+            response.insert("data".to_string(), Value::Bool(open));
+            send_io_message(Value::Object(response));
+          },
+          Err(e) => send_io_error(response, e)
         }
       }
     },
-    // "open_devtools" => {
-    //   if let Some(window) = window {
-    //     let _ = window.open_devtools();
-    //     send_io_message(Value::Object(response));
-    //   }
-    // },
-    // "close_devtools" => {
-    //   if let Some(window) = window {
-    //     let _ = window.close_devtools();
-    //     send_io_message(Value::Object(response));
-    //   }
-    // },
-    // "is_devtools_open" => {
-    //   if let Some(window) = window {
-    //     let open = window.is_devtools_open();
-    //     response.insert("data".to_string(), Value::Bool(open));
-    //     send_io_message(Value::Object(response));
-    //   }
-    // },
     "zoom" => {
       if data.is_number() {
         let data = data.as_f64().unwrap();
@@ -374,6 +775,45 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
         send_io_message(Value::Object(response));
       }
     },
+    "get_cookies" => {
+      if data.is_string() || data.is_null() {
+        if let Some(window) = window {
+          match window.get_cookies(data.as_str()) {
+            Ok(cookies) => {
+              response.insert("data".to_string(), Value::Array(cookies.iter().map(get_cookie_info).collect()));
+              send_io_message(Value::Object(response));
+            },
+            Err(e) => send_io_error(response, e.to_string())
+          }
+        }
+      }
+    },
+    "set_cookie" => {
+      if let Some(window) = window {
+        match get_cookie(data) {
+          Ok(cookie) => {
+            match window.set_cookie(&cookie) {
+              Ok(_) => send_io_message(Value::Object(response)),
+              Err(e) => send_io_error(response, e.to_string())
+            }
+          },
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    "delete_cookie" => {
+      if let Some(window) = window {
+        match get_cookie(data) {
+          Ok(cookie) => {
+            match window.delete_cookie(&cookie) {
+              Ok(_) => send_io_message(Value::Object(response)),
+              Err(e) => send_io_error(response, e.to_string())
+            }
+          },
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
     "set_background_color" => {
       if data.is_array() {
         let color = data.as_array().unwrap();
@@ -635,7 +1075,7 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
       }
     },
     "primary_monitor" => {
-      let primary = event_loop.primary_monitor();
+      let primary = platform.primary_monitor();
       if let Some(monitor) = primary {
         let data = get_monitor_info(monitor.clone());
         response.insert("data".to_string(), data);
@@ -643,7 +1083,7 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
       send_io_message(Value::Object(response));
     },
     "get_monitor_list" => {
-      let monitors = event_loop.available_monitors();
+      let monitors = platform.available_monitors();
       let mut data = Vec::new();
       for monitor in monitors {
         data.push(get_monitor_info(monitor.clone()));
@@ -657,7 +1097,7 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
           let mut monitor:Option<MonitorHandle> = None;
           if data.is_number() {
             let monitor_name = String::from("Monitor #") + data.as_number().unwrap().to_string().as_str();
-            let monitors = event_loop.available_monitors();
+            let monitors = platform.available_monitors();
             for m in monitors {
               let name = m.clone().name().unwrap();
               if name == monitor_name {
@@ -872,13 +1312,334 @@ pub fn handle_listen(app:&mut Application, str:&str, event_loop: &ActiveEventLoo
         }
       }
     },
+    "set_webview_bounds" => {
+      if let Some(window) = app.windows.get_mut(label) {
+        match Layout::from_value(data) {
+          Ok(layout) => {
+            window.set_webview_bounds(layout);
+            send_io_message(Value::Object(response));
+          },
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    "set_webview_visible" => {
+      if data.is_boolean() {
+        if let Some(window) = app.windows.get_mut(label) {
+          window.set_webview_visible(data.as_bool().unwrap());
+          send_io_message(Value::Object(response));
+        }
+      }
+    },
+    "add_webview" => {
+      if let Some(attr) = data.as_object() {
+        let id = attr.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let layout = match attr.get("bounds") {
+          Some(bounds) => Layout::from_value(bounds),
+          None => Ok(Layout::fill())
+        };
+        match layout.and_then(|layout| get_webview_attributes(attr).map(|attributes| (layout, attributes))) {
+          Ok((layout, (mut webview_attr, data_directory))) => {
+            if let Some(visible) = attr.get("visible").and_then(|v| v.as_bool()) {
+              webview_attr.visible = visible;
+            }
+            match app.add_child_webview(label.to_string(), id, webview_attr, layout, data_directory) {
+              Ok(_) => send_io_message(Value::Object(response)),
+              Err(e) => send_io_error(response, e)
+            }
+          },
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    "remove_webview" => {
+      if let Some(window) = app.windows.get_mut(label) {
+        let id = data.as_str().unwrap_or_default();
+        if window.remove_child(id) {
+          send_io_message(Value::Object(response));
+        }else{
+          send_io_error(response, format!("webview {} 不存在", id));
+        }
+      }
+    },
+    "reorder_webviews" => {
+      if let Some(window) = app.windows.get_mut(label) {
+        let ids: Vec<String> = data.as_array()
+          .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+          .unwrap_or_default();
+        match window.reorder_children(&ids) {
+          Ok(_) => send_io_message(Value::Object(response)),
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    "update_webview" => {
+      if let Some(window) = app.windows.get_mut(label) {
+        let size = window.logical_size();
+        let id = data.get("id").and_then(|v| v.as_str()).unwrap_or_default();
+        let Some(child) = window.child_mut(id) else {
+          send_io_error(response, format!("webview {} 不存在", id));
+          return;
+        };
+        if let Some(bounds) = data.get("bounds") {
+          match Layout::from_value(bounds) {
+            Ok(layout) => {
+              child.layout = layout;
+              child.apply_layout(size);
+            },
+            Err(e) => {
+              send_io_error(response, e);
+              return;
+            }
+          }
+        }
+        if let Some(visible) = data.get("visible").and_then(|v| v.as_bool()) {
+          child.set_visible(visible);
+        }
+        if let Some(url) = data.get("url").and_then(|v| v.as_str()) {
+          let _ = child.webview.load_url(url);
+        }
+        send_io_message(Value::Object(response));
+      }
+    },
+    "webviews" => {
+      if let Some(window) = window {
+        let size = window.logical_size();
+        response.insert("data".to_string(), Value::Array(window.children.iter().map(|c| c.info(size)).collect()));
+        send_io_message(Value::Object(response));
+      }
+    },
+    "set_cursor_icon" => {
+      if data.is_string() {
+        let data = data.as_str().unwrap();
+        if let Some(window) = window {
+          match data.parse::<CursorIcon>() {
+            Ok(icon) => {
+              window.set_cursor(icon);
+              send_io_message(Value::Object(response));
+            },
+            Err(_) => send_io_error(response, format!("不支持的光标图标: {}", data))
+          }
+        }
+      }
+    },
+    "set_custom_cursor" => {
+      if data.is_object() {
+        if let Some(window) = window {
+          let source = get_custom_cursor_params(data).and_then(|(path, hotspot_x, hotspot_y)| {
+            let (rgba, width, height) = load_rgba_image(&path)?;
+            let width = to_cursor_length("宽度", width as u64)?;
+            let height = to_cursor_length("高度", height as u64)?;
+            CustomCursor::from_rgba(rgba, width, height, hotspot_x, hotspot_y).map_err(|e| e.to_string())
+          });
+          match source {
+            Ok(source) => {
+              platform.set_custom_cursor(window.window.as_ref(), source);
+              send_io_message(Value::Object(response));
+            },
+            Err(e) => send_io_error(response, e)
+          }
+        }
+      }
+    },
+    "set_ime_allowed" => {
+      if data.is_boolean() {
+        if let Some(window) = window {
+          window.set_ime_allowed(data.as_bool().unwrap());
+          send_io_message(Value::Object(response));
+        }
+      }
+    },
+    "set_ime_purpose" => {
+      if data.is_string() {
+        if let Some(window) = window {
+          let purpose = match data.as_str().unwrap() {
+            "password" => ImePurpose::Password,
+            "terminal" => ImePurpose::Terminal,
+            _ => ImePurpose::Normal
+          };
+          window.set_ime_purpose(purpose);
+          send_io_message(Value::Object(response));
+        }
+      }
+    },
+    "set_ime_cursor_area" => {
+      if let Some(window) = window {
+        match get_ime_cursor_area(data) {
+          Ok((position, size)) => {
+            window.set_ime_cursor_area(position, size);
+            send_io_message(Value::Object(response));
+          },
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    "set_cursor_hittest" => {
+      if data.is_boolean() {
+        if let Some(window) = app.windows.get_mut(label) {
+          // 手动设置后不再按可点击区域切换
+          window.hittest_regions = None;
+          match window.set_cursor_hittest(data.as_bool().unwrap()) {
+            Ok(_) => send_io_message(Value::Object(response)),
+            Err(e) => send_io_error(response, e.to_string())
+          }
+        }
+      }
+    },
+    "set_cursor_visible" => {
+      if data.is_boolean() {
+        let data = data.as_bool().unwrap();
+        if let Some(window) = window {
+          window.set_cursor_visible(data);
+          send_io_message(Value::Object(response));
+        }
+      }
+    },
+    "set_cursor_grab" => {
+      if data.is_string() {
+        let data = data.as_str().unwrap();
+        if let Some(window) = window {
+          let result = get_cursor_grab_mode(data)
+            .and_then(|mode| window.set_cursor_grab(mode).map_err(|e| e.to_string()));
+          match result {
+            Ok(_) => send_io_message(Value::Object(response)),
+            Err(e) => send_io_error(response, e)
+          }
+        }
+      }
+    },
+    "set_cursor_position" => {
+      if let Some(window) = window {
+        let result = parse_position(data)
+          .and_then(|position| window.set_cursor_position(position).map_err(|e| e.to_string()));
+        match result {
+          Ok(_) => send_io_message(Value::Object(response)),
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
     "close" => {
       if let Some(_) = window {
         app.close_window(label.to_string())
       }
     },
+    "set_menu" => {
+      if window.is_some() {
+        let menu = match data {
+          Value::Null => Ok(None),
+          items => build_menu(items, &mut app.menu_items).map(Some)
+        };
+        match menu.and_then(|menu| app.set_window_menu(label, menu)) {
+          Ok(_) => send_io_message(Value::Object(response)),
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    "show_context_menu" => {
+      if let Some(window) = window {
+        // 右键菜单是临时的，其中的菜单项不需要在运行时修改
+        let menu = build_menu(data.get("items").unwrap_or(&Value::Null), &mut HashMap::new());
+        let position = data.get("position")
+          .and_then(|p| Some((p.get("x")?.as_f64()?, p.get("y")?.as_f64()?)));
+        match menu.and_then(|menu| window.window.show_context_menu(&menu, position)) {
+          Ok(id) => {
+            response.insert("data".to_string(), id.map_or(Value::Null, Value::String));
+            send_io_message(Value::Object(response));
+          },
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    // 以窗口为父窗口的对话框
+    "open_file_dialog" | "save_file_dialog" | "pick_folder" | "message_dialog" => {
+      if let Some(window) = window {
+        window.window.show_dialog(method, data, response);
+      }
+    },
+    "request_redraw" => {
+      if let Some(window) = window {
+        window.request_redraw();
+        send_io_message(Value::Object(response));
+      }
+    },
+    // node端设置的下载策略，开始下载时直接按策略处理，null为清除
+    "set_download_policy" => {
+      if window.is_some() {
+        match get_download_policy(data) {
+          Ok(policy) => {
+            set_download_policy(label, policy);
+            send_io_message(Value::Object(response));
+          },
+          Err(e) => send_io_error(response, e)
+        }
+      }
+    },
+    // 自动化操作，通过注入脚本查找、点击元素和输入文本
+    "find_element" | "click_element" | "type_text" | "get_element_text" | "wait_for_selector" => {
+      if let Some(window) = window {
+        handle_automation(window, method, data, response);
+      }
+    },
     _ => {
       println!("方法 {} 不存在", method);
     }
   }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn position_and_size_accept_fractions() {
+    assert_eq!(parse_position(&json!({ "x": 10.5, "y": -2 })), Ok(LogicalPosition::new(10.5, -2.0)));
+    assert_eq!(parse_size(&json!({ "width": 800, "height": 600.5 })), Ok(LogicalSize::new(800.0, 600.5)));
+    assert_eq!(parse_position(&json!({ "x": 1 })), Err("坐标的y必须为数字".to_string()));
+    assert_eq!(parse_size(&json!({ "width": "800", "height": 600 })), Err("尺寸的width必须为数字".to_string()));
+    assert_eq!(parse_size(&Value::Null), Err("尺寸的width必须为数字".to_string()));
+    assert_eq!(get_ime_cursor_area(&json!({ "position": { "x": 1, "y": 2 } })), Err("输入法区域必须包含position和size".to_string()));
+  }
+
+  #[test]
+  fn cursor_params_are_checked() {
+    assert_eq!(get_custom_cursor_params(&json!({ "path": "a.png", "hotspotX": 3 })), Ok(("a.png".to_string(), 3, 0)));
+    assert_eq!(get_custom_cursor_params(&json!({ "path": "a.png", "hotspotY": 70000 })), Err("光标的热点y坐标不能超过65535".to_string()));
+    assert_eq!(get_custom_cursor_params(&json!({ "path": "a.png", "hotspotX": -1 })), Err("光标的热点x坐标必须为非负整数".to_string()));
+    assert_eq!(get_custom_cursor_params(&json!({})), Err("必须设置光标图片path".to_string()));
+    assert_eq!(to_cursor_length("宽度", 65536), Err("光标的宽度不能超过65535".to_string()));
+    assert_eq!(get_cursor_grab_mode("locked"), Ok(CursorGrabMode::Locked));
+    assert_eq!(get_cursor_grab_mode("lock"), Err("无效的光标锁定模式: lock".to_string()));
+  }
+
+  #[test]
+  fn inset_rejects_invalid_lengths() {
+    assert_eq!(get_inset(&json!({ "top": 40, "left": "10%" })).map(|layout| (layout.y, layout.x)), Ok((Some(Length::Pixel(40.0)), Some(Length::Percent(10.0)))));
+    assert_eq!(get_inset(&json!({ "top": "40px" })).err(), Some("无效的长度: 40px".to_string()));
+    assert_eq!(get_inset(&json!(40)).err(), Some("webviewInset必须为对象".to_string()));
+  }
+
+  #[test]
+  fn proxy_config_requires_host_port_and_type() {
+    let proxy = get_proxy_config(&json!({ "type": "socks5", "host": "127.0.0.1", "port": 1080 }));
+    assert!(matches!(proxy, Ok(ProxyConfig::Socks5(ProxyEndpoint { ref host, ref port })) if host == "127.0.0.1" && port == "1080"));
+    assert_eq!(get_proxy_config(&json!({ "type": "http", "port": 8080 })).err(), Some("代理必须设置host".to_string()));
+    assert_eq!(get_proxy_config(&json!({ "type": "http", "host": "a", "port": 70000 })).err(), Some("代理的port必须为0-65535的整数".to_string()));
+    assert_eq!(get_proxy_config(&json!({ "type": "ftp", "host": "a", "port": 21 })).err(), Some("无效的代理类型: \"ftp\"".to_string()));
+    let attributes = get_webview_attributes(json!({ "proxy": { "type": "http" } }).as_object().unwrap());
+    assert_eq!(attributes.err(), Some("代理必须设置host".to_string()));
+    let attributes = get_webview_attributes(json!({ "headers": { "X-Token": 1 } }).as_object().unwrap());
+    assert_eq!(attributes.err(), Some("请求头 X-Token 的值必须为字符串".to_string()));
+  }
+
+  #[test]
+  fn cookies_and_headers() {
+    let cookie = get_cookie(&json!({ "name": "token", "value": "a", "sameSite": "lax", "secure": true })).unwrap();
+    assert_eq!((cookie.name(), cookie.value(), cookie.same_site(), cookie.secure()), ("token", "a", Some(SameSite::Lax), Some(true)));
+    assert_eq!(get_cookie(&json!({ "value": "a" })).err(), Some("cookie缺少name".to_string()));
+    assert_eq!(get_cookie(&json!({ "name": "a", "sameSite": "all" })).err(), Some("不支持的sameSite值: all".to_string()));
+    let headers = get_headers(&json!({ "X-Token": "a" })).unwrap();
+    assert_eq!(headers.get("x-token").unwrap(), "a");
+    assert_eq!(get_headers(&json!({ "X-Token": 1 })).err(), Some("请求头 X-Token 的值必须为字符串".to_string()));
+  }
+}
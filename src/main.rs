@@ -2,9 +2,35 @@ pub mod application;
 pub mod window;
 pub mod listen;
 pub mod event;
+pub mod download;
+pub mod webview;
+pub mod state;
+pub mod hittest;
+pub mod app_listen;
+pub mod menu;
+pub mod tray;
+pub mod shortcut;
+pub mod dialog;
+pub mod clipboard;
+pub mod notification;
+pub mod single_instance;
+pub mod deep_link;
+pub mod mock;
+pub mod script;
+pub mod automation;
+pub mod platform;
 use application::Application;
 
 fn main() {
+  // 模拟模式不创建真实的窗口，用于在没有显示器的环境中测试
+  if mock::is_mock_mode() {
+    mock::run();
+    return;
+  }
+  // 已有实例在运行时，启动参数已转发给该实例，直接退出
+  if !single_instance::init() {
+    std::process::exit(single_instance::SECOND_INSTANCE_EXIT_CODE);
+  }
   let mut app = Application::new();
   let _ = app.run();
 }
\ No newline at end of file
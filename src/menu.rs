@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use serde_json::{Map, Value};
+use muda::{AboutMetadata, CheckMenuItem, ContextMenu, IsMenuItem, Menu, MenuEvent, MenuItem, MenuItemKind, PredefinedMenuItem, Submenu};
+use muda::dpi::{LogicalPosition, Position};
+use muda::accelerator::Accelerator;
+use winit::window::Window as WinitWindow;
+use crate::event::send_app_event;
+
+// 以菜单项id为key的菜单项，用于修改菜单项的状态
+pub type MenuItems = HashMap<String, MenuItemKind>;
+
+// 显示右键菜单时为Some，记录选中的菜单项而不是发送menuItemClicked事件
+static CONTEXT_MENU_SELECTION: Mutex<Option<Option<String>>> = Mutex::new(None);
+
+#[cfg(target_os = "windows")]
+thread_local! {
+  // 以窗口句柄为key的窗口菜单，用于处理菜单快捷键
+  static WINDOW_MENUS: std::cell::RefCell<HashMap<isize, Menu>> = std::cell::RefCell::new(HashMap::new());
+}
+
+fn get_accelerator(obj: &Map<String, Value>) -> Result<Option<Accelerator>, String> {
+  match obj.get("accelerator").and_then(|v| v.as_str()) {
+    Some(accelerator) => accelerator.parse().map(Some).map_err(|e| format!("无效的快捷键 {}: {}", accelerator, e)),
+    None => Ok(None)
+  }
+}
+
+// 创建系统预定义的菜单项
+fn build_predefined_item(name: &str, text: Option<&str>) -> Result<PredefinedMenuItem, String> {
+  Ok(match name {
+    "separator" => PredefinedMenuItem::separator(),
+    "copy" => PredefinedMenuItem::copy(text),
+    "cut" => PredefinedMenuItem::cut(text),
+    "paste" => PredefinedMenuItem::paste(text),
+    "selectAll" => PredefinedMenuItem::select_all(text),
+    "undo" => PredefinedMenuItem::undo(text),
+    "redo" => PredefinedMenuItem::redo(text),
+    "minimize" => PredefinedMenuItem::minimize(text),
+    "maximize" => PredefinedMenuItem::maximize(text),
+    "fullscreen" => PredefinedMenuItem::fullscreen(text),
+    "hide" => PredefinedMenuItem::hide(text),
+    "hideOthers" => PredefinedMenuItem::hide_others(text),
+    "showAll" => PredefinedMenuItem::show_all(text),
+    "closeWindow" => PredefinedMenuItem::close_window(text),
+    "quit" => PredefinedMenuItem::quit(text),
+    "about" => PredefinedMenuItem::about(text, Some(AboutMetadata::default())),
+    "services" => PredefinedMenuItem::services(text),
+    "bringAllToFront" => PredefinedMenuItem::bring_all_to_front(text),
+    _ => return Err(format!("无效的预定义菜单项: {}", name))
+  })
+}
+
+// 根据json创建菜单项，设置了id的菜单项会记录到items中
+// { type: 'normal' | 'check' | 'separator' | 'submenu' | 'predefined', id, text, enabled, checked, accelerator, item, items }
+fn build_menu_item(item: &Value, menu_items: &mut MenuItems) -> Result<Box<dyn IsMenuItem>, String> {
+  let obj = item.as_object().ok_or(format!("无效的菜单项: {}", item))?;
+  let text = obj.get("text").and_then(|v| v.as_str()).unwrap_or_default();
+  let enabled = obj.get("enabled").and_then(|v| v.as_bool()).unwrap_or(true);
+  let id = obj.get("id").and_then(|v| v.as_str());
+  let item_type = obj.get("type").and_then(|v| v.as_str()).unwrap_or(if obj.contains_key("items") { "submenu" } else { "normal" });
+  let menu_item: Box<dyn IsMenuItem> = match item_type {
+    "normal" => {
+      let accelerator = get_accelerator(obj)?;
+      Box::new(match id {
+        Some(id) => MenuItem::with_id(id, text, enabled, accelerator),
+        None => MenuItem::new(text, enabled, accelerator)
+      })
+    },
+    "check" => {
+      let checked = obj.get("checked").and_then(|v| v.as_bool()).unwrap_or(false);
+      let accelerator = get_accelerator(obj)?;
+      Box::new(match id {
+        Some(id) => CheckMenuItem::with_id(id, text, enabled, checked, accelerator),
+        None => CheckMenuItem::new(text, enabled, checked, accelerator)
+      })
+    },
+    "separator" => Box::new(PredefinedMenuItem::separator()),
+    "predefined" => {
+      let name = obj.get("item").and_then(|v| v.as_str()).ok_or("预定义菜单项必须设置item")?;
+      let text = obj.get("text").and_then(|v| v.as_str());
+      Box::new(build_predefined_item(name, text)?)
+    },
+    "submenu" => {
+      let submenu = match id {
+        Some(id) => Submenu::with_id(id, text, enabled),
+        None => Submenu::new(text, enabled)
+      };
+      for child in build_menu_items(obj.get("items").unwrap_or(&Value::Null), menu_items)? {
+        submenu.append(child.as_ref()).map_err(|e| e.to_string())?;
+      }
+      Box::new(submenu)
+    },
+    _ => return Err(format!("无效的菜单项类型: {}", item_type))
+  };
+  if let Some(id) = id {
+    menu_items.insert(id.to_string(), menu_item.kind());
+  }
+  Ok(menu_item)
+}
+
+fn build_menu_items(items: &Value, menu_items: &mut MenuItems) -> Result<Vec<Box<dyn IsMenuItem>>, String> {
+  match items {
+    Value::Null => Ok(Vec::new()),
+    Value::Array(items) => items.iter().map(|item| build_menu_item(item, menu_items)).collect(),
+    _ => Err("菜单项必须为数组".to_string())
+  }
+}
+
+// 根据json数组创建菜单，创建成功后才记录其中设置了id的菜单项
+pub fn build_menu(items: &Value, menu_items: &mut MenuItems) -> Result<Menu, String> {
+  let menu = Menu::new();
+  let mut built = MenuItems::new();
+  for item in build_menu_items(items, &mut built)? {
+    menu.append(item.as_ref()).map_err(|e| e.to_string())?;
+  }
+  menu_items.extend(built);
+  Ok(menu)
+}
+
+// 收集菜单中的所有菜单项，包括子菜单中的
+fn collect_menu_items(items: Vec<MenuItemKind>, result: &mut MenuItems) {
+  for item in items {
+    if let MenuItemKind::Submenu(submenu) = &item {
+      collect_menu_items(submenu.items(), result);
+    }
+    result.insert(item.id().0.clone(), item);
+  }
+}
+
+// 只保留仍在使用的菜单中的菜单项，菜单被替换或移除后其中的id不能再修改
+pub fn retain_menu_items<'a>(menu_items: &mut MenuItems, menus: impl Iterator<Item = &'a Menu>) {
+  let mut live = MenuItems::new();
+  for menu in menus {
+    collect_menu_items(menu.items(), &mut live);
+  }
+  // 不同菜单使用相同id时以仍在使用的菜单项为准
+  live.retain(|id, _| menu_items.contains_key(id));
+  *menu_items = live;
+}
+
+// 修改菜单项的文字、可用状态、勾选状态和快捷键
+pub fn update_menu_item(item: &MenuItemKind, attr: &Map<String, Value>) -> Result<(), String> {
+  if let Some(text) = attr.get("text").and_then(|v| v.as_str()) {
+    match item {
+      MenuItemKind::MenuItem(i) => i.set_text(text),
+      MenuItemKind::Check(i) => i.set_text(text),
+      MenuItemKind::Submenu(i) => i.set_text(text),
+      MenuItemKind::Predefined(i) => i.set_text(text),
+      MenuItemKind::Icon(i) => i.set_text(text)
+    }
+  }
+  if let Some(enabled) = attr.get("enabled").and_then(|v| v.as_bool()) {
+    match item {
+      MenuItemKind::MenuItem(i) => i.set_enabled(enabled),
+      MenuItemKind::Check(i) => i.set_enabled(enabled),
+      MenuItemKind::Submenu(i) => i.set_enabled(enabled),
+      MenuItemKind::Icon(i) => i.set_enabled(enabled),
+      MenuItemKind::Predefined(_) => return Err("预定义菜单项不支持修改可用状态".to_string())
+    }
+  }
+  if let Some(checked) = attr.get("checked").and_then(|v| v.as_bool()) {
+    match item {
+      MenuItemKind::Check(i) => i.set_checked(checked),
+      _ => return Err("只有check菜单项可以修改勾选状态".to_string())
+    }
+  }
+  if attr.contains_key("accelerator") {
+    let accelerator = get_accelerator(attr)?;
+    let result = match item {
+      MenuItemKind::MenuItem(i) => i.set_accelerator(accelerator),
+      MenuItemKind::Check(i) => i.set_accelerator(accelerator),
+      MenuItemKind::Icon(i) => i.set_accelerator(accelerator),
+      _ => return Err("该菜单项不支持快捷键".to_string())
+    };
+    result.map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn get_hwnd(window: &WinitWindow) -> Result<isize, String> {
+  use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+  match window.window_handle().map_err(|e| e.to_string())?.as_raw() {
+    RawWindowHandle::Win32(handle) => Ok(handle.hwnd.get()),
+    _ => Err("无效的窗口句柄".to_string())
+  }
+}
+
+// 将菜单设置为窗口的菜单栏
+#[cfg(target_os = "windows")]
+pub fn init_for_window(menu: &Menu, window: &WinitWindow) -> Result<(), String> {
+  let hwnd = get_hwnd(window)?;
+  unsafe { menu.init_for_hwnd(hwnd) }.map_err(|e| e.to_string())?;
+  WINDOW_MENUS.with(|menus| menus.borrow_mut().insert(hwnd, menu.clone()));
+  Ok(())
+}
+#[cfg(not(target_os = "windows"))]
+pub fn init_for_window(_menu: &Menu, _window: &WinitWindow) -> Result<(), String> {
+  Err("当前平台不支持窗口菜单，macOS请使用应用菜单".to_string())
+}
+
+// 移除窗口的菜单栏
+#[cfg(target_os = "windows")]
+pub fn remove_for_window(menu: &Menu, window: &WinitWindow) -> Result<(), String> {
+  let hwnd = get_hwnd(window)?;
+  WINDOW_MENUS.with(|menus| menus.borrow_mut().remove(&hwnd));
+  unsafe { menu.remove_for_hwnd(hwnd) }.map_err(|e| e.to_string())
+}
+#[cfg(not(target_os = "windows"))]
+pub fn remove_for_window(_menu: &Menu, _window: &WinitWindow) -> Result<(), String> {
+  Ok(())
+}
+
+// 设置应用菜单，macOS上显示在屏幕顶部的菜单栏
+#[cfg(target_os = "macos")]
+pub fn init_for_app(menu: &Menu) {
+  menu.init_for_nsapp();
+}
+#[cfg(not(target_os = "macos"))]
+pub fn init_for_app(_menu: &Menu) {}
+
+#[cfg(target_os = "macos")]
+pub fn remove_for_app(menu: &Menu) {
+  menu.remove_for_nsapp();
+}
+#[cfg(not(target_os = "macos"))]
+pub fn remove_for_app(_menu: &Menu) {}
+
+// 在窗口内显示右键菜单，阻塞直到菜单关闭，返回选中的菜单项id
+// position为相对窗口客户区左上角的逻辑坐标，为None时显示在光标位置
+pub fn show_context_menu(menu: &Menu, window: &WinitWindow, position: Option<(f64, f64)>) -> Result<Option<String>, String> {
+  let position = position.map(|(x, y)| Position::Logical(LogicalPosition::new(x, y)));
+  *CONTEXT_MENU_SELECTION.lock().unwrap() = Some(None);
+  let result = popup_context_menu(menu, window, position);
+  let selection = CONTEXT_MENU_SELECTION.lock().unwrap().take().flatten();
+  result.map(|_| selection)
+}
+
+#[cfg(target_os = "windows")]
+fn popup_context_menu(menu: &Menu, window: &WinitWindow, position: Option<Position>) -> Result<(), String> {
+  let hwnd = get_hwnd(window)?;
+  unsafe { menu.show_context_menu_for_hwnd(hwnd, position) };
+  Ok(())
+}
+#[cfg(target_os = "macos")]
+fn popup_context_menu(menu: &Menu, window: &WinitWindow, position: Option<Position>) -> Result<(), String> {
+  use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+  match window.window_handle().map_err(|e| e.to_string())?.as_raw() {
+    RawWindowHandle::AppKit(handle) => {
+      unsafe { menu.show_context_menu_for_nsview(handle.ns_view.as_ptr(), position) };
+      Ok(())
+    },
+    _ => Err("无效的窗口句柄".to_string())
+  }
+}
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+fn popup_context_menu(_menu: &Menu, _window: &WinitWindow, _position: Option<Position>) -> Result<(), String> {
+  Err("当前平台不支持右键菜单".to_string())
+}
+
+// 处理窗口菜单的快捷键，winit的消息循环不会处理加速键表
+#[cfg(target_os = "windows")]
+pub fn translate_accelerator(msg: *const std::ffi::c_void) -> bool {
+  use windows::Win32::UI::WindowsAndMessaging::{GetAncestor, TranslateAcceleratorW, GA_ROOT, HACCEL, MSG};
+  let msg = msg as *const MSG;
+  WINDOW_MENUS.with(|menus| unsafe {
+    let root = GetAncestor((*msg).hwnd, GA_ROOT);
+    match menus.borrow().get(&(root.0 as isize)) {
+      Some(menu) => TranslateAcceleratorW(root, HACCEL(menu.haccel() as _), msg) == 1,
+      None => false
+    }
+  })
+}
+
+// 菜单项点击事件转发给node端
+pub fn listen_menu_events() {
+  MenuEvent::set_event_handler(Some(|event: MenuEvent| {
+    if let Some(selection) = CONTEXT_MENU_SELECTION.lock().unwrap().as_mut() {
+      *selection = Some(event.id().0.clone());
+      return;
+    }
+    let mut data = Map::new();
+    data.insert("id".to_string(), Value::String(event.id().0.clone()));
+    send_app_event("menuItemClicked", Some(Value::Object(data)));
+  }));
+}
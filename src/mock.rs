@@ -0,0 +1,1282 @@
+// 模拟模式，不创建真实的窗口和webview，窗口、webview和系统功能由内存中的实现代替
+// 用于在没有显示器的环境(如CI)中通过IO协议测试应用，启动参数包含 --mock 时开启
+// IO消息由和真实应用相同的handle_listen处理，参数检查、错误和事件都和真实应用一致
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::io::{self, BufRead};
+use std::rc::Rc;
+use serde_json::{json, Map, Value};
+use winit::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
+use winit::error::{ExternalError, NotSupportedError};
+use winit::event::WindowEvent;
+use winit::monitor::MonitorHandle;
+use winit::window::{Cursor, CursorGrabMode, CustomCursorSource, Fullscreen, Icon, ImePurpose, ResizeDirection, Theme, UserAttentionType, WindowAttributes, WindowButtons, WindowId, WindowLevel};
+use wry::{Rect, WebViewAttributes};
+use wry::cookie::Cookie;
+use wry::http::HeaderMap;
+use muda::{ContextMenu, Menu, MenuItemKind};
+use tray_icon::TrayIconAttributes;
+use arboard::ImageData;
+use global_hotkey::hotkey::HotKey;
+use notify_rust::{Notification, Timeout};
+use crate::application::{Application, ControlFlowMode};
+use crate::platform::{NativeWindow, NativeWebview, NativeTray, NativeClipboard, NativeHotkeys, Platform};
+use crate::listen::{IO_CHANNEL_PREFIX, handle_listen, create_response, send_io_message, send_io_error, get_cookie_info};
+use crate::event::{handle_window_event, send_window_event, send_app_event};
+use crate::app_listen::APP_LABEL;
+use crate::clipboard::Clipboard;
+use crate::shortcut::Shortcuts;
+use crate::dialog::get_message_buttons;
+use crate::deep_link::{get_url_scheme_params, get_scheme};
+
+// 没有页面时执行需要结果的脚本返回的错误
+const NO_PAGE_ERROR: &str = "模拟模式中没有页面执行脚本，可以通过mock_set_response预设结果";
+
+// 启动参数中包含--mock时使用模拟模式，--之后为应用的启动参数
+pub fn is_mock_mode() -> bool {
+  env::args().skip(1).take_while(|arg| arg != "--").any(|arg| arg == "--mock")
+}
+
+fn size_value(size: PhysicalSize<u32>) -> Value {
+  json!({ "width": size.width, "height": size.height })
+}
+
+fn optional_size_value(size: Option<Size>) -> Value {
+  size.map_or(Value::Null, |size| size_value(size.to_physical(1.0)))
+}
+
+fn window_level_value(level: WindowLevel) -> Value {
+  Value::String(match level {
+    WindowLevel::AlwaysOnBottom => "alwaysOnBottom",
+    WindowLevel::Normal => "normal",
+    WindowLevel::AlwaysOnTop => "alwaysOnTop"
+  }.to_string())
+}
+
+fn theme_value(theme: Option<Theme>) -> Value {
+  match theme {
+    Some(Theme::Light) => Value::String("light".to_string()),
+    Some(Theme::Dark) => Value::String("dark".to_string()),
+    None => Value::Null
+  }
+}
+
+fn buttons_value(buttons: WindowButtons) -> Value {
+  let names = [(WindowButtons::CLOSE, "close"), (WindowButtons::MINIMIZE, "minimize"), (WindowButtons::MAXIMIZE, "maximize")];
+  Value::Array(names.iter().filter(|(button, _)| buttons.contains(*button)).map(|(_, name)| Value::String(name.to_string())).collect())
+}
+
+// 菜单的结构，用于断言应用菜单、窗口菜单和托盘菜单
+fn menu_items_value(items: Vec<MenuItemKind>) -> Value {
+  Value::Array(items.iter().map(|item| match item {
+    MenuItemKind::MenuItem(item) => json!({ "type": "normal", "id": item.id().0, "text": item.text(), "enabled": item.is_enabled() }),
+    MenuItemKind::Check(item) => json!({ "type": "check", "id": item.id().0, "text": item.text(), "enabled": item.is_enabled(), "checked": item.is_checked() }),
+    MenuItemKind::Submenu(item) => json!({ "type": "submenu", "id": item.id().0, "text": item.text(), "enabled": item.is_enabled(), "items": menu_items_value(item.items()) }),
+    MenuItemKind::Predefined(item) => json!({ "type": "predefined", "id": item.id().0, "text": item.text() }),
+    MenuItemKind::Icon(item) => json!({ "type": "icon", "id": item.id().0, "text": item.text(), "enabled": item.is_enabled() })
+  }).collect())
+}
+
+fn menu_value(menu: Option<&Menu>) -> Value {
+  menu.map_or(Value::Null, |menu| menu_items_value(menu.items()))
+}
+
+// 模拟的桌面环境，记录所有窗口、焦点和托盘，窗口操作产生的事件在处理完IO消息后交给handle_window_event
+#[derive(Default)]
+struct Desktop {
+  next_window_id: u64,
+  windows: HashMap<WindowId, Rc<RefCell<FakeWindowState>>>,
+  focused: Option<WindowId>,
+  events: Vec<(WindowId, WindowEvent)>,
+  trays: HashMap<String, Rc<RefCell<Map<String, Value>>>>,
+  notifications: Vec<Value>,
+  url_schemes: Vec<String>,
+  exited: bool
+}
+
+impl Desktop {
+  // 聚焦窗口，之前的窗口失去焦点
+  fn focus(&mut self, id: WindowId) {
+    if self.focused == Some(id) {
+      return;
+    }
+    if let Some(previous) = self.focused.replace(id) {
+      self.events.push((previous, WindowEvent::Focused(false)));
+    }
+    self.events.push((id, WindowEvent::Focused(true)));
+  }
+}
+
+// 模拟窗口的状态，缩放比例为1，逻辑尺寸和物理尺寸相同
+struct FakeWindowState {
+  title: String,
+  inner_size: PhysicalSize<u32>,
+  position: PhysicalPosition<i32>,
+  visible: bool,
+  resizable: bool,
+  enabled_buttons: WindowButtons,
+  minimized: bool,
+  maximized: bool,
+  fullscreen: Option<Fullscreen>,
+  decorations: bool,
+  theme: Option<Theme>,
+  // 只能设置不能读取的属性，key和创建窗口的参数相同
+  properties: Map<String, Value>,
+  // 窗口内的webview，第一个为主webview
+  webviews: Vec<Rc<RefCell<FakeWebviewState>>>
+}
+
+impl FakeWindowState {
+  fn new(attr: &WindowAttributes) -> Self {
+    let properties = json!({
+      "transparent": attr.transparent,
+      "blur": attr.blur,
+      "windowLevel": window_level_value(attr.window_level),
+      "windowIcon": attr.window_icon.is_some(),
+      "contentProtected": attr.content_protected,
+      "minInnerSize": optional_size_value(attr.min_inner_size),
+      "maxInnerSize": optional_size_value(attr.max_inner_size)
+    });
+    Self {
+      title: attr.title.clone(),
+      inner_size: attr.inner_size.map_or(PhysicalSize::new(800, 600), |size| size.to_physical(1.0)),
+      position: attr.position.map_or(PhysicalPosition::new(0, 0), |position| position.to_physical(1.0)),
+      visible: attr.visible,
+      resizable: attr.resizable,
+      enabled_buttons: attr.enabled_buttons,
+      minimized: false,
+      maximized: attr.maximized,
+      fullscreen: attr.fullscreen.clone(),
+      decorations: attr.decorations,
+      theme: attr.preferred_theme,
+      properties: properties.as_object().cloned().unwrap_or_default(),
+      webviews: Vec::new()
+    }
+  }
+  // 窗口和主webview的状态，key和创建窗口的参数相同
+  fn info(&self, focused: bool) -> Map<String, Value> {
+    let mut state = self.properties.clone();
+    state.extend([
+      ("title".to_string(), Value::String(self.title.clone())),
+      ("innerSize".to_string(), size_value(self.inner_size)),
+      ("position".to_string(), json!({ "x": self.position.x, "y": self.position.y })),
+      ("visible".to_string(), Value::Bool(self.visible)),
+      ("resizable".to_string(), Value::Bool(self.resizable)),
+      ("enabledButtons".to_string(), buttons_value(self.enabled_buttons)),
+      ("minimized".to_string(), Value::Bool(self.minimized)),
+      ("maximized".to_string(), Value::Bool(self.maximized)),
+      ("fullscreen".to_string(), Value::Bool(self.fullscreen.is_some())),
+      ("borderless".to_string(), Value::Bool(!self.decorations)),
+      ("theme".to_string(), theme_value(self.theme)),
+      ("focused".to_string(), Value::Bool(focused))
+    ]);
+    if let Some(webview) = self.webviews.first() {
+      state.extend(webview.borrow().info());
+    }
+    state
+  }
+  fn scripts(&self) -> Value {
+    let scripts = self.webviews.first().map(|webview| webview.borrow().scripts.clone()).unwrap_or_default();
+    json!(scripts)
+  }
+}
+
+struct FakeWindow {
+  id: WindowId,
+  state: Rc<RefCell<FakeWindowState>>,
+  desktop: Rc<RefCell<Desktop>>
+}
+
+impl FakeWindow {
+  fn set_property(&self, key: &str, value: Value) {
+    self.state.borrow_mut().properties.insert(key.to_string(), value);
+  }
+  fn emit(&self, event: WindowEvent) {
+    self.desktop.borrow_mut().events.push((self.id, event));
+  }
+}
+
+// 窗口销毁时从桌面移除
+impl Drop for FakeWindow {
+  fn drop(&mut self) {
+    let mut desktop = self.desktop.borrow_mut();
+    desktop.windows.remove(&self.id);
+    if desktop.focused == Some(self.id) {
+      desktop.focused = None;
+    }
+  }
+}
+
+impl NativeWindow for FakeWindow {
+  fn id(&self) -> WindowId {
+    self.id
+  }
+  fn scale_factor(&self) -> f64 {
+    1.0
+  }
+  fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+    Ok(self.state.borrow().position)
+  }
+  fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+    Ok(self.state.borrow().position)
+  }
+  fn set_outer_position(&self, position: Position) {
+    let position = position.to_physical(1.0);
+    self.state.borrow_mut().position = position;
+    self.emit(WindowEvent::Moved(position));
+  }
+  fn inner_size(&self) -> PhysicalSize<u32> {
+    self.state.borrow().inner_size
+  }
+  fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+    let size = size.to_physical(1.0);
+    self.state.borrow_mut().inner_size = size;
+    self.emit(WindowEvent::Resized(size));
+    Some(size)
+  }
+  fn outer_size(&self) -> PhysicalSize<u32> {
+    self.state.borrow().inner_size
+  }
+  fn set_min_inner_size(&self, min_size: Option<Size>) {
+    self.set_property("minInnerSize", optional_size_value(min_size));
+  }
+  fn set_max_inner_size(&self, max_size: Option<Size>) {
+    self.set_property("maxInnerSize", optional_size_value(max_size));
+  }
+  fn set_title(&self, title: &str) {
+    self.state.borrow_mut().title = title.to_string();
+  }
+  fn title(&self) -> String {
+    self.state.borrow().title.clone()
+  }
+  fn set_transparent(&self, transparent: bool) {
+    self.set_property("transparent", Value::Bool(transparent));
+  }
+  fn set_blur(&self, blur: bool) {
+    self.set_property("blur", Value::Bool(blur));
+  }
+  fn set_visible(&self, visible: bool) {
+    self.state.borrow_mut().visible = visible;
+  }
+  fn is_visible(&self) -> Option<bool> {
+    Some(self.state.borrow().visible)
+  }
+  fn set_resizable(&self, resizable: bool) {
+    self.state.borrow_mut().resizable = resizable;
+  }
+  fn is_resizable(&self) -> bool {
+    self.state.borrow().resizable
+  }
+  fn set_enabled_buttons(&self, buttons: WindowButtons) {
+    self.state.borrow_mut().enabled_buttons = buttons;
+  }
+  fn enabled_buttons(&self) -> WindowButtons {
+    self.state.borrow().enabled_buttons
+  }
+  fn set_minimized(&self, minimized: bool) {
+    self.state.borrow_mut().minimized = minimized;
+  }
+  fn is_minimized(&self) -> Option<bool> {
+    Some(self.state.borrow().minimized)
+  }
+  fn set_maximized(&self, maximized: bool) {
+    self.state.borrow_mut().maximized = maximized;
+  }
+  fn is_maximized(&self) -> bool {
+    self.state.borrow().maximized
+  }
+  fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+    self.state.borrow_mut().fullscreen = fullscreen;
+  }
+  fn fullscreen(&self) -> Option<Fullscreen> {
+    self.state.borrow().fullscreen.clone()
+  }
+  fn set_decorations(&self, decorations: bool) {
+    self.state.borrow_mut().decorations = decorations;
+  }
+  fn is_decorated(&self) -> bool {
+    self.state.borrow().decorations
+  }
+  fn set_window_level(&self, level: WindowLevel) {
+    self.set_property("windowLevel", window_level_value(level));
+  }
+  fn set_window_icon(&self, icon: Option<Icon>) {
+    self.set_property("windowIcon", Value::Bool(icon.is_some()));
+  }
+  fn focus_window(&self) {
+    self.desktop.borrow_mut().focus(self.id);
+  }
+  fn has_focus(&self) -> bool {
+    self.desktop.borrow().focused == Some(self.id)
+  }
+  fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
+    let request_type = match request_type {
+      Some(UserAttentionType::Critical) => Value::String("critical".to_string()),
+      Some(UserAttentionType::Informational) => Value::String("informational".to_string()),
+      None => Value::Null
+    };
+    self.set_property("userAttention", request_type);
+  }
+  fn set_theme(&self, theme: Option<Theme>) {
+    self.state.borrow_mut().theme = theme;
+    if let Some(theme) = theme {
+      self.emit(WindowEvent::ThemeChanged(theme));
+    }
+  }
+  fn theme(&self) -> Option<Theme> {
+    self.state.borrow().theme
+  }
+  // 拖动窗口需要用户按住鼠标，模拟模式中直接返回
+  fn drag_window(&self) -> Result<(), ExternalError> {
+    Ok(())
+  }
+  fn drag_resize_window(&self, _direction: ResizeDirection) -> Result<(), ExternalError> {
+    Ok(())
+  }
+  fn show_window_menu(&self, _position: Position) {}
+  fn set_cursor(&self, cursor: Cursor) {
+    let icon = match cursor {
+      Cursor::Icon(icon) => icon.name(),
+      Cursor::Custom(_) => "custom"
+    };
+    self.set_property("cursorIcon", Value::String(icon.to_string()));
+  }
+  fn set_cursor_visible(&self, visible: bool) {
+    self.set_property("cursorVisible", Value::Bool(visible));
+  }
+  fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+    let mode = match mode {
+      CursorGrabMode::None => "none",
+      CursorGrabMode::Confined => "confined",
+      CursorGrabMode::Locked => "locked"
+    };
+    self.set_property("cursorGrab", Value::String(mode.to_string()));
+    Ok(())
+  }
+  fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
+    let position = position.to_physical::<f64>(1.0);
+    self.set_property("cursorPosition", json!({ "x": position.x, "y": position.y }));
+    Ok(())
+  }
+  fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+    self.set_property("cursorHittest", Value::Bool(hittest));
+    Ok(())
+  }
+  fn request_redraw(&self) {
+    self.emit(WindowEvent::RedrawRequested);
+  }
+  fn set_ime_allowed(&self, allowed: bool) {
+    self.set_property("imeAllowed", Value::Bool(allowed));
+  }
+  fn set_ime_purpose(&self, purpose: ImePurpose) {
+    let purpose = match purpose {
+      ImePurpose::Password => "password",
+      ImePurpose::Terminal => "terminal",
+      _ => "normal"
+    };
+    self.set_property("imePurpose", Value::String(purpose.to_string()));
+  }
+  fn set_ime_cursor_area(&self, position: Position, size: Size) {
+    let position = position.to_logical::<f64>(1.0);
+    let size = size.to_logical::<f64>(1.0);
+    self.set_property("imeCursorArea", json!({
+      "position": { "x": position.x, "y": position.y },
+      "size": { "width": size.width, "height": size.height }
+    }));
+  }
+  // 模拟模式中没有显示器
+  fn current_monitor(&self) -> Option<MonitorHandle> {
+    None
+  }
+  fn owned_window_attributes(&self, window_attr: WindowAttributes) -> WindowAttributes {
+    window_attr
+  }
+  fn set_enable(&self, enabled: bool) {
+    self.set_property("enabled", Value::Bool(enabled));
+  }
+  // 窗口菜单由Application记录，这里只需要返回成功
+  fn init_menu(&self, _menu: &Menu) -> Result<(), String> {
+    Ok(())
+  }
+  fn remove_menu(&self, _menu: &Menu) -> Result<(), String> {
+    Ok(())
+  }
+  // 没有用户选择，返回null，可以通过mock_set_response预设
+  fn show_context_menu(&self, _menu: &Menu, _position: Option<(f64, f64)>) -> Result<Option<String>, String> {
+    Ok(None)
+  }
+  fn show_dialog(&self, method: &str, data: &Value, response: Map<String, Value>) {
+    show_fake_dialog(method, data, response);
+  }
+  fn build_webview(&self, webview_attr: WebViewAttributes) -> Result<Box<dyn NativeWebview>, String> {
+    let webview = Rc::new(RefCell::new(FakeWebviewState::new(&webview_attr)));
+    self.state.borrow_mut().webviews.push(webview.clone());
+    Ok(Box::new(FakeWebview { state: webview }))
+  }
+}
+
+// 模拟webview的状态
+struct FakeWebviewState {
+  url: String,
+  headers: Map<String, Value>,
+  zoom: f64,
+  devtools: bool,
+  background_color: Option<(u8, u8, u8, u8)>,
+  cookies: Vec<Cookie<'static>>,
+  bounds: Option<Rect>,
+  visible: bool,
+  // 执行过的脚本
+  scripts: Vec<String>
+}
+
+fn headers_value(headers: &HeaderMap) -> Map<String, Value> {
+  headers.iter()
+    .map(|(name, value)| (name.to_string(), Value::String(value.to_str().unwrap_or_default().to_string())))
+    .collect()
+}
+
+// url的主机名
+fn url_host(url: &str) -> &str {
+  let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+  rest.split(['/', ':', '?', '#']).next().unwrap_or_default()
+}
+
+// 没有设置domain的cookie属于所有url，设置了domain时也属于它的子域名
+fn cookie_matches_host(cookie: &Cookie, host: &str) -> bool {
+  match cookie.domain() {
+    Some(domain) => {
+      let domain = domain.trim_start_matches('.');
+      host == domain || host.ends_with(&format!(".{}", domain))
+    },
+    None => true
+  }
+}
+
+fn is_same_cookie(a: &Cookie, b: &Cookie) -> bool {
+  a.name() == b.name() && a.domain() == b.domain() && a.path() == b.path()
+}
+
+impl FakeWebviewState {
+  fn new(attr: &WebViewAttributes) -> Self {
+    Self {
+      url: attr.url.clone().unwrap_or_default(),
+      headers: attr.headers.as_ref().map(headers_value).unwrap_or_default(),
+      zoom: 1.0,
+      devtools: false,
+      background_color: attr.background_color,
+      cookies: Vec::new(),
+      bounds: attr.bounds,
+      visible: attr.visible,
+      scripts: Vec::new()
+    }
+  }
+  fn info(&self) -> Map<String, Value> {
+    let bounds = self.bounds.map_or(Value::Null, |bounds| {
+      let position = bounds.position.to_logical::<f64>(1.0);
+      let size = bounds.size.to_logical::<f64>(1.0);
+      json!({ "x": position.x, "y": position.y, "width": size.width, "height": size.height })
+    });
+    let info = json!({
+      "url": self.url,
+      "headers": self.headers,
+      "zoom": self.zoom,
+      "devtools": self.devtools,
+      "backgroundColor": self.background_color.map(|(r, g, b, a)| [r, g, b, a]),
+      "cookies": self.cookies.iter().map(get_cookie_info).collect::<Vec<_>>(),
+      "webviewVisible": self.visible,
+      "webviewBounds": bounds
+    });
+    info.as_object().cloned().unwrap_or_default()
+  }
+}
+
+struct FakeWebview {
+  state: Rc<RefCell<FakeWebviewState>>
+}
+
+impl NativeWebview for FakeWebview {
+  fn load_url(&self, url: &str) -> Result<(), String> {
+    let mut state = self.state.borrow_mut();
+    state.url = url.to_string();
+    state.headers.clear();
+    Ok(())
+  }
+  fn load_url_with_headers(&self, url: &str, headers: HeaderMap) -> Result<(), String> {
+    let mut state = self.state.borrow_mut();
+    state.url = url.to_string();
+    state.headers = headers_value(&headers);
+    Ok(())
+  }
+  fn url(&self) -> Result<String, String> {
+    Ok(self.state.borrow().url.clone())
+  }
+  fn evaluate_script(&self, js: &str) -> Result<(), String> {
+    self.state.borrow_mut().scripts.push(js.to_string());
+    Ok(())
+  }
+  // 没有页面返回结果，脚本记录后返回错误
+  fn evaluate_script_with_callback(&self, js: &str, _callback: Box<dyn Fn(String) + Send>) -> Result<(), String> {
+    self.state.borrow_mut().scripts.push(js.to_string());
+    Err(NO_PAGE_ERROR.to_string())
+  }
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  fn open_devtools(&self) {
+    self.state.borrow_mut().devtools = true;
+  }
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  fn close_devtools(&self) {
+    self.state.borrow_mut().devtools = false;
+  }
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  fn is_devtools_open(&self) -> bool {
+    self.state.borrow().devtools
+  }
+  fn zoom(&self, scale_factor: f64) -> Result<(), String> {
+    self.state.borrow_mut().zoom = scale_factor;
+    Ok(())
+  }
+  fn clear_all_browsing_data(&self) -> Result<(), String> {
+    self.state.borrow_mut().cookies.clear();
+    Ok(())
+  }
+  fn cookies(&self, url: Option<&str>) -> Result<Vec<Cookie<'static>>, String> {
+    let state = self.state.borrow();
+    Ok(match url {
+      Some(url) => state.cookies.iter().filter(|cookie| cookie_matches_host(cookie, url_host(url))).cloned().collect(),
+      None => state.cookies.clone()
+    })
+  }
+  fn set_cookie(&self, cookie: &Cookie) -> Result<(), String> {
+    let mut state = self.state.borrow_mut();
+    state.cookies.retain(|c| !is_same_cookie(c, cookie));
+    state.cookies.push(cookie.clone().into_owned());
+    Ok(())
+  }
+  fn delete_cookie(&self, cookie: &Cookie) -> Result<(), String> {
+    self.state.borrow_mut().cookies.retain(|c| !is_same_cookie(c, cookie));
+    Ok(())
+  }
+  fn set_background_color(&self, color: (u8, u8, u8, u8)) -> Result<(), String> {
+    self.state.borrow_mut().background_color = Some(color);
+    Ok(())
+  }
+  fn set_bounds(&self, bounds: Rect) -> Result<(), String> {
+    self.state.borrow_mut().bounds = Some(bounds);
+    Ok(())
+  }
+  fn set_visible(&self, visible: bool) -> Result<(), String> {
+    self.state.borrow_mut().visible = visible;
+    Ok(())
+  }
+  // 子webview的层级由Window记录
+  fn bring_to_front(&self) -> Result<(), String> {
+    Ok(())
+  }
+}
+
+// 模拟托盘图标，状态的key和创建托盘的参数相同
+struct FakeTray {
+  id: String,
+  state: Rc<RefCell<Map<String, Value>>>,
+  desktop: Rc<RefCell<Desktop>>
+}
+
+impl FakeTray {
+  fn set_property(&self, key: &str, value: Value) {
+    self.state.borrow_mut().insert(key.to_string(), value);
+  }
+}
+
+// 托盘移除时从桌面移除
+impl Drop for FakeTray {
+  fn drop(&mut self) {
+    self.desktop.borrow_mut().trays.remove(&self.id);
+  }
+}
+
+fn optional_string_value(value: Option<&str>) -> Value {
+  value.map_or(Value::Null, |value| Value::String(value.to_string()))
+}
+
+impl NativeTray for FakeTray {
+  fn set_icon(&self, icon: Option<tray_icon::Icon>) -> Result<(), String> {
+    self.set_property("icon", Value::Bool(icon.is_some()));
+    Ok(())
+  }
+  fn set_tooltip(&self, tooltip: Option<&str>) -> Result<(), String> {
+    self.set_property("tooltip", optional_string_value(tooltip));
+    Ok(())
+  }
+  fn set_title(&self, title: Option<&str>) {
+    self.set_property("title", optional_string_value(title));
+  }
+  // 托盘菜单由Tray记录
+  fn set_menu(&self, _menu: Option<Box<dyn ContextMenu>>) {}
+  fn set_show_menu_on_left_click(&self, enable: bool) {
+    self.set_property("menuOnLeftClick", Value::Bool(enable));
+  }
+  fn set_visible(&self, visible: bool) -> Result<(), String> {
+    self.set_property("visible", Value::Bool(visible));
+    Ok(())
+  }
+}
+
+// 模拟剪贴板，和系统剪贴板一样写入新内容时清除其他格式的内容
+#[derive(Default)]
+struct FakeClipboard {
+  text: Option<String>,
+  html: Option<String>,
+  image: Option<ImageData<'static>>
+}
+
+impl NativeClipboard for FakeClipboard {
+  fn get_text(&mut self) -> Result<String, arboard::Error> {
+    self.text.clone().ok_or(arboard::Error::ContentNotAvailable)
+  }
+  fn set_text(&mut self, text: &str) -> Result<(), arboard::Error> {
+    *self = Self { text: Some(text.to_string()), ..Default::default() };
+    Ok(())
+  }
+  fn get_html(&mut self) -> Result<String, arboard::Error> {
+    self.html.clone().ok_or(arboard::Error::ContentNotAvailable)
+  }
+  // altText同时作为纯文本
+  fn set_html(&mut self, html: &str, alt_text: Option<&str>) -> Result<(), arboard::Error> {
+    *self = Self { text: alt_text.map(|text| text.to_string()), html: Some(html.to_string()), image: None };
+    Ok(())
+  }
+  fn get_image(&mut self) -> Result<ImageData<'static>, arboard::Error> {
+    self.image.clone().ok_or(arboard::Error::ContentNotAvailable)
+  }
+  fn set_image(&mut self, image: ImageData<'static>) -> Result<(), arboard::Error> {
+    *self = Self { image: Some(image), ..Default::default() };
+    Ok(())
+  }
+  fn clear(&mut self) -> Result<(), arboard::Error> {
+    *self = Self::default();
+    Ok(())
+  }
+}
+
+// 模拟全局快捷键，和系统一样同一个快捷键不能重复注册
+#[derive(Default)]
+struct FakeHotkeys {
+  registered: RefCell<HashSet<u32>>
+}
+
+impl NativeHotkeys for FakeHotkeys {
+  fn register(&self, hotkey: HotKey) -> global_hotkey::Result<()> {
+    if !self.registered.borrow_mut().insert(hotkey.id()) {
+      return Err(global_hotkey::Error::AlreadyRegistered(hotkey));
+    }
+    Ok(())
+  }
+  fn unregister(&self, hotkey: HotKey) -> global_hotkey::Result<()> {
+    self.registered.borrow_mut().remove(&hotkey.id());
+    Ok(())
+  }
+  fn unregister_all(&self, hotkeys: &[HotKey]) -> global_hotkey::Result<()> {
+    let mut registered = self.registered.borrow_mut();
+    for hotkey in hotkeys {
+      registered.remove(&hotkey.id());
+    }
+    Ok(())
+  }
+}
+
+// 没有用户操作，消息对话框返回ok，文件对话框返回null，可以通过mock_set_response预设
+fn show_fake_dialog(method: &str, data: &Value, mut response: Map<String, Value>) {
+  let result = match method {
+    "message_dialog" => get_message_buttons(data.get("buttons")).map(|_| Value::String("ok".to_string())),
+    _ => Ok(Value::Null)
+  };
+  match result {
+    Ok(data) => {
+      response.insert("data".to_string(), data);
+      send_io_message(Value::Object(response));
+    },
+    Err(e) => send_io_error(response, e)
+  }
+}
+
+// 模拟的事件循环和系统功能
+#[derive(Default)]
+pub struct FakePlatform {
+  desktop: Rc<RefCell<Desktop>>
+}
+
+impl Platform for FakePlatform {
+  fn create_window(&self, window_attr: WindowAttributes) -> Result<Box<dyn NativeWindow>, String> {
+    let mut desktop = self.desktop.borrow_mut();
+    desktop.next_window_id += 1;
+    let id = WindowId::from(desktop.next_window_id);
+    let state = Rc::new(RefCell::new(FakeWindowState::new(&window_attr)));
+    desktop.windows.insert(id, state.clone());
+    // 和真实窗口一样，显示的窗口创建后获得焦点
+    if window_attr.active && window_attr.visible {
+      desktop.focus(id);
+    }
+    Ok(Box::new(FakeWindow { id, state, desktop: self.desktop.clone() }))
+  }
+  // 模拟模式中没有显示器
+  fn available_monitors(&self) -> Vec<MonitorHandle> {
+    Vec::new()
+  }
+  fn primary_monitor(&self) -> Option<MonitorHandle> {
+    None
+  }
+  // 没有事件循环创建自定义光标，只记录设置了自定义光标
+  fn set_custom_cursor(&self, window: &dyn NativeWindow, _source: CustomCursorSource) {
+    if let Some(state) = self.desktop.borrow().windows.get(&window.id()) {
+      state.borrow_mut().properties.insert("cursorIcon".to_string(), Value::String("custom".to_string()));
+    }
+  }
+  fn exit(&self) {
+    self.desktop.borrow_mut().exited = true;
+  }
+  fn create_tray(&self, id: &str, tray_attr: TrayIconAttributes) -> Result<Box<dyn NativeTray>, String> {
+    let state = json!({
+      "icon": tray_attr.icon.is_some(),
+      "tooltip": tray_attr.tooltip,
+      "title": tray_attr.title,
+      "menuOnLeftClick": tray_attr.menu_on_left_click,
+      "visible": true
+    });
+    let state = Rc::new(RefCell::new(state.as_object().cloned().unwrap_or_default()));
+    self.desktop.borrow_mut().trays.insert(id.to_string(), state.clone());
+    Ok(Box::new(FakeTray { id: id.to_string(), state, desktop: self.desktop.clone() }))
+  }
+  // 记录通知后直接响应通知id
+  fn show_notification(&self, id: String, notification: Notification, mut response: Map<String, Value>) {
+    let actions: Vec<Value> = notification.actions.chunks(2)
+      .map(|action| json!({ "id": action[0], "label": action.get(1) }))
+      .collect();
+    let timeout = match notification.timeout {
+      Timeout::Default => Value::Null,
+      Timeout::Never => json!(0),
+      Timeout::Milliseconds(timeout) => json!(timeout)
+    };
+    self.desktop.borrow_mut().notifications.push(json!({
+      "id": id,
+      "title": notification.summary,
+      "body": notification.body,
+      "icon": Some(notification.icon).filter(|icon| !icon.is_empty()),
+      "actions": actions,
+      "timeout": timeout
+    }));
+    response.insert("data".to_string(), Value::String(id));
+    send_io_message(Value::Object(response));
+  }
+  fn register_url_scheme(&self, data: &Value) -> Result<(), String> {
+    let (scheme, _, _) = get_url_scheme_params(data)?;
+    let mut desktop = self.desktop.borrow_mut();
+    if !desktop.url_schemes.contains(&scheme) {
+      desktop.url_schemes.push(scheme);
+    }
+    Ok(())
+  }
+  fn unregister_url_scheme(&self, data: &Value) -> Result<(), String> {
+    let scheme = get_scheme(data)?;
+    self.desktop.borrow_mut().url_schemes.retain(|s| s != scheme);
+    Ok(())
+  }
+  fn show_dialog(&self, method: &str, data: &Value, response: Map<String, Value>) {
+    show_fake_dialog(method, data, response);
+  }
+}
+
+fn control_flow_value(mode: ControlFlowMode) -> Value {
+  match mode {
+    ControlFlowMode::Wait => json!({ "mode": "wait" }),
+    ControlFlowMode::Poll => json!({ "mode": "poll" }),
+    ControlFlowMode::Interval(interval) => json!({ "mode": "waitUntil", "interval": interval.as_millis() as u64 })
+  }
+}
+
+// 模拟应用，使用模拟的窗口和系统功能运行真实的Application
+pub struct MockApplication {
+  pub app: Application,
+  pub platform: FakePlatform,
+  // 通过mock_set_response预设的响应，key为 label:method，使用一次后删除
+  responses: HashMap<String, Map<String, Value>>
+}
+
+impl Default for MockApplication {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl MockApplication {
+  pub fn new() -> Self {
+    let mut app = Application::new();
+    app.clipboard = Clipboard::with_native(Box::new(FakeClipboard::default()));
+    app.shortcuts = Shortcuts::with_manager(Box::new(FakeHotkeys::default()));
+    Self {
+      app,
+      platform: FakePlatform::default(),
+      responses: HashMap::new()
+    }
+  }
+  // 处理一条IO消息，然后处理消息产生的窗口事件
+  pub fn handle(&mut self, str: &str) {
+    let message: Value = match serde_json::from_str(str) {
+      Ok(message) => message,
+      Err(e) => {
+        println!("消息格式错误: {}", e);
+        return;
+      }
+    };
+    let (Some(id), Some(label), Some(method)) = (message["id"].as_str(), message["label"].as_str(), message["method"].as_str()) else {
+      println!("消息格式错误: {}", str);
+      return;
+    };
+    let data = message.get("data").unwrap_or(&Value::Null);
+    let mut response = create_response(id, label, method);
+    // 使用预设的响应
+    if let Some(preset) = self.responses.remove(&format!("{}:{}", label, method)) {
+      response.extend(preset);
+      send_io_message(Value::Object(response));
+      return;
+    }
+    if label == APP_LABEL && method.starts_with("mock_") {
+      self.handle_mock(method, data, response);
+    }else{
+      handle_listen(&mut self.app, str, &self.platform);
+    }
+    self.dispatch_events();
+  }
+  // 窗口事件处理时可能产生新的事件(如焦点转移到模态子窗口)，直到没有事件为止
+  fn dispatch_events(&mut self) {
+    loop {
+      let events = std::mem::take(&mut self.platform.desktop.borrow_mut().events);
+      if events.is_empty() {
+        break;
+      }
+      for (window_id, event) in events {
+        handle_window_event(&mut self.app, &self.platform, window_id, event);
+      }
+    }
+  }
+  // 模拟模式专用的方法
+  fn handle_mock(&mut self, method: &str, data: &Value, mut response: Map<String, Value>) {
+    match method {
+      // 模拟窗口事件 { label, method, data }
+      "mock_emit_window_event" => {
+        let label = data.get("label").and_then(|v| v.as_str()).unwrap_or_default();
+        let method = data.get("method").and_then(|v| v.as_str()).unwrap_or_default();
+        send_io_message(Value::Object(response));
+        send_window_event(label, method, data.get("data").cloned());
+      },
+      // 模拟应用事件 { method, data }
+      "mock_emit_app_event" => {
+        let method = data.get("method").and_then(|v| v.as_str()).unwrap_or_default();
+        send_io_message(Value::Object(response));
+        send_app_event(method, data.get("data").cloned());
+      },
+      // 预设下一次调用方法时的响应 { label, method, data, error }，label默认为应用
+      "mock_set_response" => {
+        let label = data.get("label").and_then(|v| v.as_str()).unwrap_or(APP_LABEL);
+        let Some(method) = data.get("method").and_then(|v| v.as_str()) else {
+          send_io_error(response, "必须设置method".to_string());
+          return;
+        };
+        let mut preset = Map::new();
+        for key in ["data", "error"] {
+          if let Some(value) = data.get(key) {
+            preset.insert(key.to_string(), value.clone());
+          }
+        }
+        self.responses.insert(format!("{}:{}", label, method), preset);
+        send_io_message(Value::Object(response));
+      },
+      // 获取所有窗口和应用的状态
+      "mock_get_state" => {
+        response.insert("data".to_string(), self.state());
+        send_io_message(Value::Object(response));
+      },
+      _ => {
+        send_io_error(response, format!("方法 {} 不存在", method));
+      }
+    }
+  }
+  // 获取当前状态，用于测试断言
+  pub fn state(&mut self) -> Value {
+    let desktop = self.platform.desktop.borrow();
+    let mut windows = Map::new();
+    for (label, window) in &self.app.windows {
+      let Some(fake) = desktop.windows.get(&window.id()) else {
+        continue;
+      };
+      let fake = fake.borrow();
+      let mut state = fake.info(desktop.focused == Some(window.id()));
+      state.insert("menu".to_string(), menu_value(window.menu.as_ref()));
+      let id: u64 = window.id().into();
+      let size = window.logical_size();
+      windows.insert(label.clone(), json!({
+        "id": id.to_string(),
+        "parent": window.parent,
+        "state": state,
+        "scripts": fake.scripts(),
+        "webviews": window.children.iter().map(|child| child.info(size)).collect::<Vec<_>>()
+      }));
+    }
+    let trays: Map<String, Value> = self.app.trays.iter().filter_map(|(id, tray)| {
+      let mut state = desktop.trays.get(id)?.borrow().clone();
+      state.insert("menu".to_string(), menu_value(tray.menu.as_ref()));
+      Some((id.clone(), Value::Object(state)))
+    }).collect();
+    let shortcuts: Map<String, Value> = self.app.shortcuts.hotkeys().iter()
+      .map(|(id, hotkey)| (id.clone(), Value::String(hotkey.to_string())))
+      .collect();
+    let state = json!({
+      "controlFlow": control_flow_value(self.app.control_flow()),
+      "appMenu": menu_value(self.app.app_menu.as_ref()),
+      "clipboardText": self.app.clipboard.read_text().unwrap_or_default(),
+      "clipboardHtml": self.app.clipboard.read_html().unwrap_or_default(),
+      "clipboardImage": self.app.clipboard.read_image().unwrap_or_default(),
+      "urlSchemes": desktop.url_schemes
+    });
+    json!({
+      "windows": windows,
+      "state": state,
+      "trays": trays,
+      "shortcuts": shortcuts,
+      "notifications": desktop.notifications
+    })
+  }
+}
+
+// 以模拟模式运行，不创建事件循环，在当前线程中处理IO消息
+pub fn run() {
+  let mut app = MockApplication::new();
+  for line in io::stdin().lock().lines() {
+    match line {
+      Ok(line) => {
+        if let Some(string) = line.strip_prefix(IO_CHANNEL_PREFIX) {
+          app.handle(string);
+        }
+      },
+      Err(e) => {
+        println!("接受消息错误: {:?}", e);
+        break;
+      }
+    }
+    // 和真实应用一样，最后一个窗口关闭后退出
+    if app.platform.desktop.borrow().exited {
+      break;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::listen::{record_io_messages, take_io_messages};
+
+  // 按顺序处理脚本中的每一行消息，返回发送的所有IO消息
+  fn run_script(app: &mut MockApplication, lines: &[Value]) -> Vec<Value> {
+    record_io_messages();
+    for line in lines {
+      app.handle(&line.to_string());
+    }
+    take_io_messages()
+  }
+
+  fn request(id: &str, label: &str, method: &str, data: Value) -> Value {
+    json!({ "id": id, "label": label, "method": method, "data": data })
+  }
+
+  // 找到对应id的响应
+  fn find_response<'a>(messages: &'a [Value], id: &str) -> Option<&'a Value> {
+    messages.iter().find(|m| m["type"] == "response" && m["id"] == id)
+  }
+
+  #[test]
+  fn create_and_query_window() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "main", "create", json!({ "title": "hello", "innerSize": { "width": 400, "height": 300 } })),
+      request("2", "main", "title", Value::Null),
+      request("3", "main", "set_title", json!("world")),
+      request("4", "main", "title", Value::Null),
+      request("5", "main", "inner_size", Value::Null),
+      request("6", "main", "is_visible", Value::Null),
+      request("7", "main", "set_ime_purpose", json!("password")),
+      request("8", "main", "set_cursor_icon", json!("pointer"))
+    ]);
+    assert_eq!(find_response(&messages, "1").unwrap()["data"], "1");
+    assert_eq!(find_response(&messages, "2").unwrap()["data"], "hello");
+    assert!(find_response(&messages, "3").unwrap().get("data").is_none());
+    assert_eq!(find_response(&messages, "4").unwrap()["data"], "world");
+    assert_eq!(find_response(&messages, "5").unwrap()["data"], json!({ "width": 400, "height": 300 }));
+    assert_eq!(find_response(&messages, "6").unwrap()["data"], true);
+    let state = &app.state()["windows"]["main"]["state"];
+    assert_eq!((&state["imePurpose"], &state["cursorIcon"]), (&json!("password"), &json!("pointer")));
+  }
+
+  #[test]
+  fn missing_window_and_invalid_params_do_not_respond() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "missing", "title", Value::Null),
+      request("2", "main", "create", Value::Null),
+      request("3", "main", "set_visible", json!("yes")),
+      request("4", "main", "create", Value::Null)
+    ]);
+    assert!(find_response(&messages, "1").is_none());
+    assert!(find_response(&messages, "3").is_none());
+    assert!(find_response(&messages, "4").is_none());
+  }
+
+  #[test]
+  fn state_changes_emit_window_events() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "main", "create", Value::Null),
+      request("2", "other", "create", Value::Null),
+      request("3", "main", "set_inner_size", json!({ "width": 1024, "height": 768 })),
+      request("4", "main", "set_outer_position", json!({ "x": 10, "y": 20 })),
+      request("5", "main", "focus_window", Value::Null)
+    ]);
+    let events: Vec<(&str, &str)> = messages.iter()
+      .filter(|m| m["type"] == "windowEvent")
+      .map(|m| (m["label"].as_str().unwrap(), m["method"].as_str().unwrap()))
+      .collect();
+    assert_eq!(events, vec![
+      ("main", "focus"),
+      ("main", "blur"),
+      ("other", "focus"),
+      ("main", "resize"),
+      ("main", "move"),
+      ("other", "blur"),
+      ("main", "focus")
+    ]);
+    assert_eq!(find_response(&messages, "3").unwrap()["data"], json!({ "width": 1024, "height": 768 }));
+    // resize事件由真实的事件处理按布局调整了主webview
+    let state = &app.state()["windows"]["main"]["state"];
+    assert_eq!(state["webviewBounds"], json!({ "x": 0.0, "y": 0.0, "width": 1024.0, "height": 768.0 }));
+    assert_eq!(state["position"], json!({ "x": 10, "y": 20 }));
+  }
+
+  #[test]
+  fn close_window_closes_children() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "main", "create", Value::Null),
+      request("2", "child", "create", json!({ "parent": "main" })),
+      request("3", "orphan", "create", json!({ "parent": "missing" })),
+      request("4", "main", "close", Value::Null)
+    ]);
+    assert_eq!(find_response(&messages, "3").unwrap()["error"], "父窗口 missing 不存在");
+    assert!(messages.iter().any(|m| m["type"] == "windowEvent" && m["label"] == "child" && m["method"] == "close"));
+    assert!(app.app.windows.is_empty());
+    assert!(app.platform.desktop.borrow().windows.is_empty());
+  }
+
+  #[test]
+  fn invalid_params_return_shared_errors() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "main", "create", json!({ "proxy": { "type": "http" } })),
+      request("2", "main", "create", Value::Null),
+      request("3", "main", "set_ime_cursor_area", json!({ "position": { "x": 1, "y": 2 }, "size": { "width": 10 } })),
+      request("4", "main", "set_cursor_position", json!({ "x": 1 })),
+      request("5", "main", "evaluate", json!({ "timeout": 100 })),
+      request("6", "main", "set_cookie", json!({ "value": "a" })),
+      request("7", "main", "set_cursor_icon", json!("x")),
+      request("8", "main", "set_cursor_grab", json!("lock")),
+      request("9", "main", "set_webview_bounds", json!({ "width": "40px" })),
+      request("10", "main", "message_dialog", json!({ "buttons": "maybe" }))
+    ]);
+    assert_eq!(find_response(&messages, "1").unwrap()["error"], "代理必须设置host");
+    assert_eq!(find_response(&messages, "3").unwrap()["error"], "尺寸的height必须为数字");
+    assert_eq!(find_response(&messages, "4").unwrap()["error"], "坐标的y必须为数字");
+    assert_eq!(find_response(&messages, "5").unwrap()["error"], "必须设置script");
+    assert_eq!(find_response(&messages, "6").unwrap()["error"], "cookie缺少name");
+    assert_eq!(find_response(&messages, "7").unwrap()["error"], "不支持的光标图标: x");
+    assert_eq!(find_response(&messages, "8").unwrap()["error"], "无效的光标锁定模式: lock");
+    assert_eq!(find_response(&messages, "9").unwrap()["error"], "无效的长度: 40px");
+    assert_eq!(find_response(&messages, "10").unwrap()["error"], "无效的按钮: maybe");
+  }
+
+  #[test]
+  fn child_webviews() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "main", "create", json!({ "innerSize": { "width": 800, "height": 600 } })),
+      request("2", "main", "add_webview", json!({ "id": "side", "url": "https://example.com", "bounds": { "width": "25%" } })),
+      request("3", "main", "add_webview", json!({ "id": "side" })),
+      request("4", "main", "add_webview", json!({ "id": "top", "bounds": { "height": 40 } })),
+      request("5", "main", "reorder_webviews", json!(["side"])),
+      request("6", "main", "update_webview", json!({ "id": "top", "visible": false })),
+      request("7", "main", "webviews", Value::Null),
+      request("8", "main", "remove_webview", json!("missing"))
+    ]);
+    assert_eq!(find_response(&messages, "3").unwrap()["error"], "webview side 已存在");
+    assert_eq!(find_response(&messages, "7").unwrap()["data"], json!([
+      { "id": "top", "x": 0.0, "y": 0.0, "width": 800.0, "height": 40.0, "visible": false, "url": "" },
+      { "id": "side", "x": 0.0, "y": 0.0, "width": 200.0, "height": 600.0, "visible": true, "url": "https://example.com" }
+    ]));
+    assert_eq!(find_response(&messages, "8").unwrap()["error"], "webview missing 不存在");
+  }
+
+  #[test]
+  fn cookies_are_kept_per_webview() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "main", "create", Value::Null),
+      request("2", "main", "set_cookie", json!({ "name": "token", "value": "a", "domain": "example.com" })),
+      request("3", "main", "set_cookie", json!({ "name": "token", "value": "b", "domain": "example.com" })),
+      request("4", "main", "set_cookie", json!({ "name": "lang", "value": "zh", "domain": "other.com" })),
+      request("5", "main", "get_cookies", json!("https://www.example.com/a")),
+      request("6", "main", "delete_cookie", json!({ "name": "token", "domain": "example.com" })),
+      request("7", "main", "get_cookies", Value::Null)
+    ]);
+    let names = |id: &str| -> Vec<(String, String)> {
+      find_response(&messages, id).unwrap()["data"].as_array().unwrap().iter()
+        .map(|c| (c["name"].as_str().unwrap().to_string(), c["value"].as_str().unwrap().to_string()))
+        .collect()
+    };
+    assert_eq!(names("5"), vec![("token".to_string(), "b".to_string())]);
+    assert_eq!(names("7"), vec![("lang".to_string(), "zh".to_string())]);
+  }
+
+  #[test]
+  fn evaluate_scripts_are_recorded() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "main", "create", Value::Null),
+      request("2", "main", "evaluate_script", json!("document.title = 'a'")),
+      request("3", APP_LABEL, "mock_set_response", json!({ "label": "main", "method": "evaluate_script_with_callback", "data": "\"a\"" })),
+      request("4", "main", "evaluate_script_with_callback", json!("document.title")),
+      request("5", "main", "evaluate_script_with_callback", json!("document.title")),
+      request("6", "main", "evaluate", json!({ "script": "location.href", "timeout": 1000 })),
+      request("7", APP_LABEL, "mock_set_response", json!({ "label": "main", "method": "evaluate", "data": { "id": 1 } })),
+      request("8", "main", "evaluate", json!({ "script": "fetch('/user').then(res => res.json())" })),
+      request("9", "main", "evaluate", json!({}))
+    ]);
+    let state = app.state();
+    let scripts = state["windows"]["main"]["scripts"].as_array().unwrap();
+    assert_eq!(scripts.len(), 3);
+    assert_eq!((&scripts[0], &scripts[1]), (&json!("document.title = 'a'"), &json!("document.title")));
+    assert!(scripts[2].as_str().unwrap().contains("location.href"));
+    assert_eq!(find_response(&messages, "4").unwrap()["data"], "\"a\"");
+    assert_eq!(find_response(&messages, "5").unwrap()["error"], NO_PAGE_ERROR);
+    assert_eq!(find_response(&messages, "6").unwrap()["error"], NO_PAGE_ERROR);
+    assert_eq!(find_response(&messages, "8").unwrap()["data"], json!({ "id": 1 }));
+    assert_eq!(find_response(&messages, "9").unwrap()["error"], "必须设置script");
+  }
+
+  #[test]
+  fn app_commands() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", APP_LABEL, "set_control_flow", json!({ "mode": "waitUntil" })),
+      request("2", APP_LABEL, "set_control_flow", json!({ "mode": "waitUntil", "interval": 100 })),
+      request("3", APP_LABEL, "register_shortcut", json!({ "accelerator": "Alt+Space" })),
+      request("4", APP_LABEL, "register_shortcut", json!({ "accelerator": "Alt+Space" })),
+      request("5", APP_LABEL, "register_shortcut", json!({ "id": "search", "accelerator": "Alt+Space" })),
+      request("6", APP_LABEL, "write_clipboard_html", json!({ "html": "<b>a</b>", "altText": "a" })),
+      request("7", APP_LABEL, "read_clipboard_html", Value::Null),
+      request("8", APP_LABEL, "read_clipboard_text", Value::Null),
+      request("9", APP_LABEL, "read_clipboard_image", Value::Null),
+      request("10", APP_LABEL, "unknown", Value::Null),
+      request("11", APP_LABEL, "register_url_scheme", json!({ "scheme": "../../x", "exec": "app" })),
+      request("12", APP_LABEL, "register_url_scheme", json!({ "scheme": "myapp", "exec": "app" })),
+      request("13", APP_LABEL, "notify", json!({ "id": "n1", "title": "hi", "actions": [{ "id": "ok" }], "timeout": 0 })),
+      request("14", APP_LABEL, "notify", json!({ "urgency": "high" }))
+    ]);
+    assert_eq!(find_response(&messages, "1").unwrap()["error"], "waitUntil模式必须设置interval");
+    assert_eq!(find_response(&messages, "3").unwrap()["data"], "Alt+Space");
+    assert_eq!(find_response(&messages, "4").unwrap()["error"], "快捷键 Alt+Space 已注册");
+    // 同一个快捷键不能用不同的id重复注册
+    assert!(find_response(&messages, "5").unwrap()["error"].is_string());
+    assert_eq!(find_response(&messages, "7").unwrap()["data"], "<b>a</b>");
+    assert_eq!(find_response(&messages, "8").unwrap()["data"], "a");
+    assert_eq!(find_response(&messages, "9").unwrap()["data"], Value::Null);
+    assert_eq!(find_response(&messages, "10").unwrap()["error"], "方法 unknown 不存在");
+    assert_eq!(find_response(&messages, "11").unwrap()["error"], "无效的协议名: ../../x");
+    assert_eq!(find_response(&messages, "13").unwrap()["data"], "n1");
+    assert_eq!(find_response(&messages, "14").unwrap()["error"], "无效的通知紧急程度: high");
+    let state = app.state();
+    assert_eq!(state["state"]["controlFlow"], json!({ "mode": "waitUntil", "interval": 100 }));
+    assert_eq!(state["state"]["urlSchemes"], json!(["myapp"]));
+    assert_eq!(state["shortcuts"].as_object().unwrap().len(), 1);
+    assert_eq!(state["notifications"], json!([
+      { "id": "n1", "title": "hi", "body": "", "icon": null, "actions": [{ "id": "ok", "label": "ok" }], "timeout": 0 }
+    ]));
+  }
+
+  // macOS上菜单只能在主线程中创建，测试线程中无法运行
+  #[cfg(not(target_os = "macos"))]
+  #[test]
+  fn menus_are_validated_and_tracked() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", APP_LABEL, "set_app_menu", json!([{ "text": "File", "items": [{ "id": "open", "text": "Open" }] }])),
+      request("2", APP_LABEL, "update_menu_item", json!({ "id": "open", "enabled": false })),
+      request("3", APP_LABEL, "update_menu_item", json!({ "id": "save" })),
+      request("4", APP_LABEL, "set_app_menu", json!("File")),
+      request("5", "main", "create", Value::Null),
+      request("6", "main", "set_menu", json!([{ "type": "bad" }])),
+      request("7", APP_LABEL, "create_tray", json!({ "id": "tray", "menu": [{ "type": "predefined" }] })),
+      request("8", APP_LABEL, "create_tray", json!({ "id": "tray", "tooltip": "hi", "menu": [{ "id": "quit", "text": "Quit" }] })),
+      request("9", APP_LABEL, "set_app_menu", Value::Null),
+      request("10", APP_LABEL, "update_menu_item", json!({ "id": "open", "enabled": true })),
+      request("11", APP_LABEL, "update_menu_item", json!({ "id": "quit", "text": "Exit" }))
+    ]);
+    assert!(find_response(&messages, "2").unwrap().get("error").is_none());
+    assert_eq!(find_response(&messages, "3").unwrap()["error"], "菜单项 save 不存在");
+    assert_eq!(find_response(&messages, "4").unwrap()["error"], "菜单项必须为数组");
+    assert_eq!(find_response(&messages, "6").unwrap()["error"], "无效的菜单项类型: bad");
+    assert_eq!(find_response(&messages, "7").unwrap()["error"], "预定义菜单项必须设置item");
+    assert!(find_response(&messages, "8").unwrap().get("error").is_none());
+    assert_eq!(find_response(&messages, "10").unwrap()["error"], "菜单项 open 不存在");
+    assert!(find_response(&messages, "11").unwrap().get("error").is_none());
+    let state = app.state();
+    assert_eq!(state["windows"]["main"]["state"]["menu"], Value::Null);
+    assert_eq!(state["state"]["appMenu"], Value::Null);
+    let tray = &state["trays"]["tray"];
+    assert_eq!(tray["tooltip"], "hi");
+    assert_eq!(tray["menu"][0]["text"], "Exit");
+  }
+
+  #[test]
+  fn mock_events_and_preset_responses() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "main", "create", Value::Null),
+      request("2", APP_LABEL, "mock_set_response", json!({ "method": "open_file_dialog", "data": ["/tmp/a.txt"] })),
+      request("3", APP_LABEL, "open_file_dialog", json!({ "multiple": true })),
+      request("4", APP_LABEL, "open_file_dialog", json!({ "multiple": true })),
+      request("5", APP_LABEL, "mock_set_response", json!({ "label": "main", "method": "title", "error": "失败" })),
+      request("6", "main", "title", Value::Null),
+      request("7", APP_LABEL, "mock_emit_window_event", json!({ "label": "main", "method": "cursorEnter" })),
+      request("8", APP_LABEL, "mock_emit_app_event", json!({ "method": "openUrl", "data": "myapp://open" })),
+      request("9", "main", "message_dialog", json!({ "message": "ok?" }))
+    ]);
+    assert_eq!(find_response(&messages, "3").unwrap()["data"], json!(["/tmp/a.txt"]));
+    assert_eq!(find_response(&messages, "4").unwrap()["data"], Value::Null);
+    assert_eq!(find_response(&messages, "6").unwrap()["error"], "失败");
+    assert!(messages.contains(&json!({ "type": "windowEvent", "label": "main", "method": "cursorEnter" })));
+    assert!(messages.contains(&json!({ "type": "appEvent", "method": "openUrl", "data": "myapp://open" })));
+    assert_eq!(find_response(&messages, "9").unwrap()["data"], "ok");
+  }
+
+  #[test]
+  fn automation_uses_preset_elements() {
+    let mut app = MockApplication::new();
+    let messages = run_script(&mut app, &[
+      request("1", "main", "create", Value::Null),
+      request("2", "main", "click_element", json!({ "selector": "#submit" })),
+      request("3", APP_LABEL, "mock_set_response", json!({ "label": "main", "method": "get_element_text", "data": "hello" })),
+      request("4", "main", "get_element_text", json!({ "selector": "#title" })),
+      request("5", "main", "wait_for_selector", json!({}))
+    ]);
+    assert_eq!(find_response(&messages, "2").unwrap()["error"], NO_PAGE_ERROR);
+    assert_eq!(find_response(&messages, "4").unwrap()["data"], "hello");
+    assert_eq!(find_response(&messages, "5").unwrap()["error"], "必须设置selector");
+  }
+}
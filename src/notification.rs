@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::sync::mpsc::{self, RecvTimeoutError};
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::time::Duration;
+use serde_json::{Map, Value};
+use notify_rust::{Notification, NotificationResponse, Timeout, Urgency};
+use crate::event::send_app_event;
+use crate::listen::{send_io_message, send_io_error};
+use crate::platform::Platform;
+
+// 未设置id时自动生成的通知id
+static NOTIFICATION_ID: AtomicUsize = AtomicUsize::new(0);
+// 等待用户操作的最长时间，超过后关闭通知，避免不自动关闭的通知一直占用线程
+#[cfg(all(unix, not(target_os = "macos")))]
+const RESPONSE_WAIT_LIMIT: Duration = Duration::from_secs(60 * 60);
+
+// 获取通知的紧急程度
+fn get_urgency(urgency: &str) -> Result<Urgency, String> {
+  match urgency {
+    "low" => Ok(Urgency::Low),
+    "normal" => Ok(Urgency::Normal),
+    "critical" => Ok(Urgency::Critical),
+    _ => Err(format!("无效的通知紧急程度: {}", urgency))
+  }
+}
+
+// 获取通知自动关闭的时间，0为不自动关闭
+fn get_timeout(timeout: &Value) -> Result<Timeout, String> {
+  let timeout = timeout.as_u64().ok_or("timeout必须为非负整数")?;
+  match u32::try_from(timeout) {
+    Ok(0) => Ok(Timeout::Never),
+    Ok(timeout) => Ok(Timeout::Milliseconds(timeout)),
+    Err(_) => Err(format!("timeout不能超过{}", u32::MAX))
+  }
+}
+
+// 创建通知
+// { title, body, icon, actions: [{ id, label }], timeout, urgency }
+fn build_notification(data: &Value) -> Result<Notification, String> {
+  let mut notification = Notification::new();
+  if let Some(title) = data.get("title").and_then(|v| v.as_str()) {
+    notification.summary(title);
+  }
+  if let Some(body) = data.get("body").and_then(|v| v.as_str()) {
+    notification.body(body);
+  }
+  // Linux上为图标名称或路径，Windows上显示为通知图片，macOS不支持
+  if let Some(icon) = data.get("icon").and_then(|v| v.as_str()) {
+    notification.icon(icon);
+    #[cfg(target_os = "windows")]
+    notification.image_path(icon);
+  }
+  match data.get("actions") {
+    None | Some(Value::Null) => {},
+    Some(Value::Array(actions)) => for action in actions {
+      let id = action.get("id").and_then(|v| v.as_str()).ok_or("通知按钮的id必须为字符串")?;
+      let label = action.get("label").and_then(|v| v.as_str()).unwrap_or(id);
+      notification.action(id, label);
+    },
+    Some(_) => return Err("actions必须为数组".to_string())
+  }
+  match data.get("timeout") {
+    None | Some(Value::Null) => {},
+    Some(timeout) => {
+      notification.timeout(get_timeout(timeout)?);
+    }
+  }
+  // macOS不支持
+  if let Some(urgency) = data.get("urgency").filter(|v| !v.is_null()) {
+    let urgency = get_urgency(urgency.as_str().ok_or("urgency必须为字符串")?)?;
+    #[cfg(not(target_os = "macos"))]
+    notification.urgency(urgency);
+    #[cfg(target_os = "macos")]
+    let _ = urgency;
+  }
+  Ok(notification)
+}
+
+// 等待用户操作，Linux上超过等待时间后关闭通知
+#[cfg(all(unix, not(target_os = "macos")))]
+fn wait_for_response(handle: notify_rust::NotificationHandle, handler: impl FnOnce(&NotificationResponse)) {
+  let (done, finished) = mpsc::channel::<()>();
+  thread::scope(|scope| {
+    let handle = &handle;
+    // 关闭通知后通知服务发出关闭信号，等待随之结束
+    scope.spawn(move || {
+      if let Err(RecvTimeoutError::Timeout) = finished.recv_timeout(RESPONSE_WAIT_LIMIT) {
+        pollster::block_on(handle.close_async());
+      }
+    });
+    pollster::block_on(handle.wait_for_action_async(handler));
+    drop(done);
+  });
+}
+
+// macOS和Windows上的通知不会一直保留，等待会随通知关闭结束
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn wait_for_response(handle: notify_rust::NotificationHandle, handler: impl FnOnce(&NotificationResponse)) {
+  let _ = handle.wait_for_response(handler);
+}
+
+// 发送通知，通知显示后响应通知id，用户点击通知或按钮时发送事件给node端
+pub fn show_notification(platform: &dyn Platform, data: &Value, response: Map<String, Value>) {
+  let id = match data.get("id").and_then(|v| v.as_str()) {
+    Some(id) => id.to_string(),
+    None => format!("notification-{}", NOTIFICATION_ID.fetch_add(1, Ordering::Relaxed))
+  };
+  match build_notification(data) {
+    Ok(notification) => platform.show_notification(id, notification, response),
+    Err(e) => send_io_error(response, e)
+  }
+}
+
+// 显示系统通知，Linux上通过freedesktop的D-Bus通知接口发送
+pub fn send_notification(id: String, notification: Notification, mut response: Map<String, Value>) {
+  // 发送通知和等待用户操作都会阻塞，在子线程中进行
+  thread::spawn(move || {
+    let handle = match notification.show() {
+      Ok(handle) => handle,
+      Err(e) => {
+        send_io_error(response, e.to_string());
+        return;
+      }
+    };
+    response.insert("data".to_string(), Value::String(id.clone()));
+    send_io_message(Value::Object(response));
+    wait_for_response(handle, |result: &NotificationResponse| {
+      let mut data = Map::new();
+      data.insert("id".to_string(), Value::String(id));
+      match result {
+        NotificationResponse::Default => send_app_event("notificationClicked", Some(Value::Object(data))),
+        NotificationResponse::Action(action) => {
+          data.insert("action".to_string(), Value::String(action.clone()));
+          send_app_event("notificationAction", Some(Value::Object(data)));
+        },
+        _ => {}
+      }
+    });
+  });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn builds_notification_from_options() {
+    let notification = build_notification(&json!({
+      "title": "下载完成",
+      "body": "文件已保存",
+      "actions": [{ "id": "open", "label": "打开" }, { "id": "ignore" }],
+      "timeout": 5000
+    })).unwrap();
+    assert_eq!(notification.summary, "下载完成");
+    assert_eq!(notification.body, "文件已保存");
+    assert_eq!(notification.actions, vec!["open", "打开", "ignore", "ignore"]);
+    assert_eq!(notification.timeout, Timeout::Milliseconds(5000));
+    let notification = build_notification(&json!({ "title": "提示", "timeout": 0, "actions": null })).unwrap();
+    assert_eq!(notification.timeout, Timeout::Never);
+    assert!(notification.actions.is_empty());
+  }
+
+  #[test]
+  fn parses_urgency() {
+    assert_eq!(get_urgency("low"), Ok(Urgency::Low));
+    assert_eq!(get_urgency("normal"), Ok(Urgency::Normal));
+    assert_eq!(get_urgency("critical"), Ok(Urgency::Critical));
+    assert_eq!(get_urgency("high").unwrap_err(), "无效的通知紧急程度: high");
+    assert!(build_notification(&json!({ "title": "提示", "urgency": "critical" })).is_ok());
+    assert_eq!(build_notification(&json!({ "title": "提示", "urgency": 2 })).unwrap_err(), "urgency必须为字符串");
+  }
+
+  #[test]
+  fn rejects_invalid_options() {
+    assert_eq!(build_notification(&json!({ "timeout": -1 })).unwrap_err(), "timeout必须为非负整数");
+    assert_eq!(build_notification(&json!({ "timeout": 5000000000u64 })).unwrap_err(), "timeout不能超过4294967295");
+    assert_eq!(build_notification(&json!({ "actions": {} })).unwrap_err(), "actions必须为数组");
+    assert_eq!(build_notification(&json!({ "actions": [{ "label": "打开" }] })).unwrap_err(), "通知按钮的id必须为字符串");
+  }
+}
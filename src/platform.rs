@@ -0,0 +1,535 @@
+// 处理IO消息的代码通过这里的接口操作窗口、webview和系统功能
+// 真实应用中由winit、wry等实现，模拟模式中由mock.rs中的内存实现，两者使用相同的消息处理代码
+use serde_json::{Map, Value};
+use winit::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
+use winit::error::{ExternalError, NotSupportedError};
+use winit::event_loop::ActiveEventLoop;
+use winit::monitor::MonitorHandle;
+use winit::raw_window_handle::{HasWindowHandle, RawWindowHandle};
+use winit::window::{Cursor, CursorGrabMode, CustomCursorSource, Fullscreen, Icon, ImePurpose, ResizeDirection, Theme, UserAttentionType, Window as WinitWindow, WindowAttributes, WindowButtons, WindowId, WindowLevel};
+use wry::{Rect, WebView, WebViewAttributes, WebViewBuilder};
+use wry::cookie::Cookie;
+use wry::http::HeaderMap;
+#[cfg(target_os = "macos")]
+use wry::WebViewBuilderExtDarwin;
+#[cfg(target_os = "macos")]
+use crate::webview::data_store_identifier;
+use muda::{ContextMenu, Menu};
+use tray_icon::{TrayIcon, TrayIconAttributes};
+use arboard::{Clipboard as ArboardClipboard, ImageData};
+use global_hotkey::GlobalHotKeyManager;
+use global_hotkey::hotkey::HotKey;
+use notify_rust::Notification;
+use crate::menu::{init_for_window, remove_for_window, show_context_menu};
+use crate::dialog::show_dialog;
+use crate::notification::send_notification;
+use crate::deep_link::{register_url_scheme, unregister_url_scheme};
+
+// 窗口
+pub trait NativeWindow {
+  fn id(&self) -> WindowId;
+  fn scale_factor(&self) -> f64;
+  fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError>;
+  fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError>;
+  fn set_outer_position(&self, position: Position);
+  fn inner_size(&self) -> PhysicalSize<u32>;
+  fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>>;
+  fn outer_size(&self) -> PhysicalSize<u32>;
+  fn set_min_inner_size(&self, min_size: Option<Size>);
+  fn set_max_inner_size(&self, max_size: Option<Size>);
+  fn set_title(&self, title: &str);
+  fn title(&self) -> String;
+  fn set_transparent(&self, transparent: bool);
+  fn set_blur(&self, blur: bool);
+  fn set_visible(&self, visible: bool);
+  fn is_visible(&self) -> Option<bool>;
+  fn set_resizable(&self, resizable: bool);
+  fn is_resizable(&self) -> bool;
+  fn set_enabled_buttons(&self, buttons: WindowButtons);
+  fn enabled_buttons(&self) -> WindowButtons;
+  fn set_minimized(&self, minimized: bool);
+  fn is_minimized(&self) -> Option<bool>;
+  fn set_maximized(&self, maximized: bool);
+  fn is_maximized(&self) -> bool;
+  fn set_fullscreen(&self, fullscreen: Option<Fullscreen>);
+  fn fullscreen(&self) -> Option<Fullscreen>;
+  fn set_decorations(&self, decorations: bool);
+  fn is_decorated(&self) -> bool;
+  fn set_window_level(&self, level: WindowLevel);
+  fn set_window_icon(&self, icon: Option<Icon>);
+  fn focus_window(&self);
+  fn has_focus(&self) -> bool;
+  fn request_user_attention(&self, request_type: Option<UserAttentionType>);
+  fn set_theme(&self, theme: Option<Theme>);
+  fn theme(&self) -> Option<Theme>;
+  fn drag_window(&self) -> Result<(), ExternalError>;
+  fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError>;
+  fn show_window_menu(&self, position: Position);
+  fn set_cursor(&self, cursor: Cursor);
+  fn set_cursor_visible(&self, visible: bool);
+  fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError>;
+  fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError>;
+  fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError>;
+  fn request_redraw(&self);
+  fn set_ime_allowed(&self, allowed: bool);
+  fn set_ime_purpose(&self, purpose: ImePurpose);
+  fn set_ime_cursor_area(&self, position: Position, size: Size);
+  fn current_monitor(&self) -> Option<MonitorHandle>;
+  // 以下为需要窗口句柄的操作
+  // 以该窗口为父窗口的窗口属性
+  fn owned_window_attributes(&self, window_attr: WindowAttributes) -> WindowAttributes;
+  // 设置窗口是否可以接收输入
+  fn set_enable(&self, enabled: bool);
+  // 设置和移除窗口菜单栏
+  fn init_menu(&self, menu: &Menu) -> Result<(), String>;
+  fn remove_menu(&self, menu: &Menu) -> Result<(), String>;
+  // 显示右键菜单，返回选中的菜单项id
+  fn show_context_menu(&self, menu: &Menu, position: Option<(f64, f64)>) -> Result<Option<String>, String>;
+  // 显示以该窗口为父窗口的对话框，结果直接作为响应发送
+  fn show_dialog(&self, method: &str, data: &Value, response: Map<String, Value>);
+  // 在窗口内创建webview
+  fn build_webview(&self, webview_attr: WebViewAttributes) -> Result<Box<dyn NativeWebview>, String>;
+}
+
+// webview
+pub trait NativeWebview {
+  fn load_url(&self, url: &str) -> Result<(), String>;
+  fn load_url_with_headers(&self, url: &str, headers: HeaderMap) -> Result<(), String>;
+  fn url(&self) -> Result<String, String>;
+  fn evaluate_script(&self, js: &str) -> Result<(), String>;
+  fn evaluate_script_with_callback(&self, js: &str, callback: Box<dyn Fn(String) + Send>) -> Result<(), String>;
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  fn open_devtools(&self);
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  fn close_devtools(&self);
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  fn is_devtools_open(&self) -> bool;
+  fn zoom(&self, scale_factor: f64) -> Result<(), String>;
+  fn clear_all_browsing_data(&self) -> Result<(), String>;
+  // 获取cookie，传入url时只返回该url对应的cookie
+  fn cookies(&self, url: Option<&str>) -> Result<Vec<Cookie<'static>>, String>;
+  fn set_cookie(&self, cookie: &Cookie) -> Result<(), String>;
+  fn delete_cookie(&self, cookie: &Cookie) -> Result<(), String>;
+  fn set_background_color(&self, color: (u8, u8, u8, u8)) -> Result<(), String>;
+  fn set_bounds(&self, bounds: Rect) -> Result<(), String>;
+  fn set_visible(&self, visible: bool) -> Result<(), String>;
+  // 移动到窗口内所有webview的最上层
+  fn bring_to_front(&self) -> Result<(), String>;
+}
+
+// 托盘图标
+pub trait NativeTray {
+  fn set_icon(&self, icon: Option<tray_icon::Icon>) -> Result<(), String>;
+  fn set_tooltip(&self, tooltip: Option<&str>) -> Result<(), String>;
+  fn set_title(&self, title: Option<&str>);
+  fn set_menu(&self, menu: Option<Box<dyn ContextMenu>>);
+  fn set_show_menu_on_left_click(&self, enable: bool);
+  fn set_visible(&self, visible: bool) -> Result<(), String>;
+}
+
+// 剪贴板
+pub trait NativeClipboard {
+  fn get_text(&mut self) -> Result<String, arboard::Error>;
+  fn set_text(&mut self, text: &str) -> Result<(), arboard::Error>;
+  fn get_html(&mut self) -> Result<String, arboard::Error>;
+  fn set_html(&mut self, html: &str, alt_text: Option<&str>) -> Result<(), arboard::Error>;
+  fn get_image(&mut self) -> Result<ImageData<'static>, arboard::Error>;
+  fn set_image(&mut self, image: ImageData<'static>) -> Result<(), arboard::Error>;
+  fn clear(&mut self) -> Result<(), arboard::Error>;
+}
+
+// 全局快捷键
+pub trait NativeHotkeys {
+  fn register(&self, hotkey: HotKey) -> global_hotkey::Result<()>;
+  fn unregister(&self, hotkey: HotKey) -> global_hotkey::Result<()>;
+  fn unregister_all(&self, hotkeys: &[HotKey]) -> global_hotkey::Result<()>;
+}
+
+// 事件循环和不属于任何窗口的系统功能
+pub trait Platform {
+  fn create_window(&self, window_attr: WindowAttributes) -> Result<Box<dyn NativeWindow>, String>;
+  fn available_monitors(&self) -> Vec<MonitorHandle>;
+  fn primary_monitor(&self) -> Option<MonitorHandle>;
+  // 自定义光标需要通过事件循环创建
+  fn set_custom_cursor(&self, window: &dyn NativeWindow, source: CustomCursorSource);
+  fn exit(&self);
+  fn create_tray(&self, id: &str, tray_attr: TrayIconAttributes) -> Result<Box<dyn NativeTray>, String>;
+  // 显示通知，结果直接作为响应发送
+  fn show_notification(&self, id: String, notification: Notification, response: Map<String, Value>);
+  fn register_url_scheme(&self, data: &Value) -> Result<(), String>;
+  fn unregister_url_scheme(&self, data: &Value) -> Result<(), String>;
+  // 显示不属于任何窗口的对话框，结果直接作为响应发送
+  fn show_dialog(&self, method: &str, data: &Value, response: Map<String, Value>);
+}
+
+impl NativeWindow for WinitWindow {
+  fn id(&self) -> WindowId {
+    WinitWindow::id(self)
+  }
+  fn scale_factor(&self) -> f64 {
+    WinitWindow::scale_factor(self)
+  }
+  fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+    WinitWindow::inner_position(self)
+  }
+  fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+    WinitWindow::outer_position(self)
+  }
+  fn set_outer_position(&self, position: Position) {
+    WinitWindow::set_outer_position(self, position)
+  }
+  fn inner_size(&self) -> PhysicalSize<u32> {
+    WinitWindow::inner_size(self)
+  }
+  fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+    WinitWindow::request_inner_size(self, size)
+  }
+  fn outer_size(&self) -> PhysicalSize<u32> {
+    WinitWindow::outer_size(self)
+  }
+  fn set_min_inner_size(&self, min_size: Option<Size>) {
+    WinitWindow::set_min_inner_size(self, min_size)
+  }
+  fn set_max_inner_size(&self, max_size: Option<Size>) {
+    WinitWindow::set_max_inner_size(self, max_size)
+  }
+  fn set_title(&self, title: &str) {
+    WinitWindow::set_title(self, title)
+  }
+  fn title(&self) -> String {
+    WinitWindow::title(self)
+  }
+  fn set_transparent(&self, transparent: bool) {
+    WinitWindow::set_transparent(self, transparent)
+  }
+  fn set_blur(&self, blur: bool) {
+    WinitWindow::set_blur(self, blur)
+  }
+  fn set_visible(&self, visible: bool) {
+    WinitWindow::set_visible(self, visible)
+  }
+  fn is_visible(&self) -> Option<bool> {
+    WinitWindow::is_visible(self)
+  }
+  fn set_resizable(&self, resizable: bool) {
+    WinitWindow::set_resizable(self, resizable)
+  }
+  fn is_resizable(&self) -> bool {
+    WinitWindow::is_resizable(self)
+  }
+  fn set_enabled_buttons(&self, buttons: WindowButtons) {
+    WinitWindow::set_enabled_buttons(self, buttons)
+  }
+  fn enabled_buttons(&self) -> WindowButtons {
+    WinitWindow::enabled_buttons(self)
+  }
+  fn set_minimized(&self, minimized: bool) {
+    WinitWindow::set_minimized(self, minimized)
+  }
+  fn is_minimized(&self) -> Option<bool> {
+    WinitWindow::is_minimized(self)
+  }
+  fn set_maximized(&self, maximized: bool) {
+    WinitWindow::set_maximized(self, maximized)
+  }
+  fn is_maximized(&self) -> bool {
+    WinitWindow::is_maximized(self)
+  }
+  fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
+    WinitWindow::set_fullscreen(self, fullscreen)
+  }
+  fn fullscreen(&self) -> Option<Fullscreen> {
+    WinitWindow::fullscreen(self)
+  }
+  fn set_decorations(&self, decorations: bool) {
+    WinitWindow::set_decorations(self, decorations)
+  }
+  fn is_decorated(&self) -> bool {
+    WinitWindow::is_decorated(self)
+  }
+  fn set_window_level(&self, level: WindowLevel) {
+    WinitWindow::set_window_level(self, level)
+  }
+  fn set_window_icon(&self, icon: Option<Icon>) {
+    WinitWindow::set_window_icon(self, icon)
+  }
+  fn focus_window(&self) {
+    WinitWindow::focus_window(self)
+  }
+  fn has_focus(&self) -> bool {
+    WinitWindow::has_focus(self)
+  }
+  fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
+    WinitWindow::request_user_attention(self, request_type)
+  }
+  fn set_theme(&self, theme: Option<Theme>) {
+    WinitWindow::set_theme(self, theme)
+  }
+  fn theme(&self) -> Option<Theme> {
+    WinitWindow::theme(self)
+  }
+  fn drag_window(&self) -> Result<(), ExternalError> {
+    WinitWindow::drag_window(self)
+  }
+  fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+    WinitWindow::drag_resize_window(self, direction)
+  }
+  fn show_window_menu(&self, position: Position) {
+    WinitWindow::show_window_menu(self, position)
+  }
+  fn set_cursor(&self, cursor: Cursor) {
+    WinitWindow::set_cursor(self, cursor)
+  }
+  fn set_cursor_visible(&self, visible: bool) {
+    WinitWindow::set_cursor_visible(self, visible)
+  }
+  fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+    WinitWindow::set_cursor_grab(self, mode)
+  }
+  fn set_cursor_position(&self, position: Position) -> Result<(), ExternalError> {
+    WinitWindow::set_cursor_position(self, position)
+  }
+  fn set_cursor_hittest(&self, hittest: bool) -> Result<(), ExternalError> {
+    WinitWindow::set_cursor_hittest(self, hittest)
+  }
+  fn request_redraw(&self) {
+    WinitWindow::request_redraw(self)
+  }
+  fn set_ime_allowed(&self, allowed: bool) {
+    WinitWindow::set_ime_allowed(self, allowed)
+  }
+  fn set_ime_purpose(&self, purpose: ImePurpose) {
+    WinitWindow::set_ime_purpose(self, purpose)
+  }
+  fn set_ime_cursor_area(&self, position: Position, size: Size) {
+    WinitWindow::set_ime_cursor_area(self, position, size)
+  }
+  fn current_monitor(&self) -> Option<MonitorHandle> {
+    WinitWindow::current_monitor(self)
+  }
+  // 创建的窗口始终位于父窗口之上(Linux上不设置)
+  fn owned_window_attributes(&self, window_attr: WindowAttributes) -> WindowAttributes {
+    let Ok(handle) = self.window_handle() else {
+      return window_attr;
+    };
+    match handle.as_raw() {
+      #[cfg(target_os = "windows")]
+      RawWindowHandle::Win32(handle) => {
+        use winit::platform::windows::WindowAttributesExtWindows;
+        window_attr.with_owner_window(handle.hwnd.get())
+      },
+      // macOS上会作为父窗口的child window，跟随父窗口移动
+      #[cfg(target_os = "macos")]
+      raw @ RawWindowHandle::AppKit(_) => unsafe { window_attr.with_parent_window(Some(raw)) },
+      // X11上设置父窗口会使窗口被限制在父窗口的客户区内，所以不设置
+      _ => window_attr
+    }
+  }
+  // 仅Windows支持
+  fn set_enable(&self, enabled: bool) {
+    #[cfg(target_os = "windows")]
+    {
+      use winit::platform::windows::WindowExtWindows;
+      WindowExtWindows::set_enable(self, enabled);
+    }
+    #[cfg(not(target_os = "windows"))]
+    let _ = enabled;
+  }
+  fn init_menu(&self, menu: &Menu) -> Result<(), String> {
+    init_for_window(menu, self)
+  }
+  fn remove_menu(&self, menu: &Menu) -> Result<(), String> {
+    remove_for_window(menu, self)
+  }
+  fn show_context_menu(&self, menu: &Menu, position: Option<(f64, f64)>) -> Result<Option<String>, String> {
+    show_context_menu(menu, self, position)
+  }
+  fn show_dialog(&self, method: &str, data: &Value, response: Map<String, Value>) {
+    show_dialog(method, data, response, Some(self))
+  }
+  fn build_webview(&self, webview_attr: WebViewAttributes) -> Result<Box<dyn NativeWebview>, String> {
+    // macOS不使用WebContext的数据目录，需要通过数据存储标识区分(macOS 14+)
+    #[cfg(target_os = "macos")]
+    let data_store = webview_attr.context.as_ref().and_then(|context| context.data_directory()).map(data_store_identifier);
+    #[allow(unused_mut)]
+    let mut builder = WebViewBuilder::new_with_attributes(webview_attr);
+    #[cfg(target_os = "macos")]
+    if let Some(identifier) = data_store {
+      builder = builder.with_data_store_identifier(identifier);
+    }
+    // 直接使用build()创建的webview会导致winit窗口崩溃，需要创建child webview
+    let webview = builder.build_as_child(self).map_err(|e| e.to_string())?;
+    Ok(Box::new(webview))
+  }
+}
+
+impl NativeWebview for WebView {
+  fn load_url(&self, url: &str) -> Result<(), String> {
+    WebView::load_url(self, url).map_err(|e| e.to_string())
+  }
+  fn load_url_with_headers(&self, url: &str, headers: HeaderMap) -> Result<(), String> {
+    WebView::load_url_with_headers(self, url, headers).map_err(|e| e.to_string())
+  }
+  fn url(&self) -> Result<String, String> {
+    WebView::url(self).map_err(|e| e.to_string())
+  }
+  fn evaluate_script(&self, js: &str) -> Result<(), String> {
+    WebView::evaluate_script(self, js).map_err(|e| e.to_string())
+  }
+  fn evaluate_script_with_callback(&self, js: &str, callback: Box<dyn Fn(String) + Send>) -> Result<(), String> {
+    WebView::evaluate_script_with_callback(self, js, callback).map_err(|e| e.to_string())
+  }
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  fn open_devtools(&self) {
+    WebView::open_devtools(self)
+  }
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  fn close_devtools(&self) {
+    WebView::close_devtools(self)
+  }
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  fn is_devtools_open(&self) -> bool {
+    WebView::is_devtools_open(self)
+  }
+  fn zoom(&self, scale_factor: f64) -> Result<(), String> {
+    WebView::zoom(self, scale_factor).map_err(|e| e.to_string())
+  }
+  fn clear_all_browsing_data(&self) -> Result<(), String> {
+    WebView::clear_all_browsing_data(self).map_err(|e| e.to_string())
+  }
+  fn cookies(&self, url: Option<&str>) -> Result<Vec<Cookie<'static>>, String> {
+    match url {
+      Some(url) => WebView::cookies_for_url(self, url),
+      None => WebView::cookies(self)
+    }.map_err(|e| e.to_string())
+  }
+  fn set_cookie(&self, cookie: &Cookie) -> Result<(), String> {
+    WebView::set_cookie(self, cookie).map_err(|e| e.to_string())
+  }
+  fn delete_cookie(&self, cookie: &Cookie) -> Result<(), String> {
+    WebView::delete_cookie(self, cookie).map_err(|e| e.to_string())
+  }
+  fn set_background_color(&self, color: (u8, u8, u8, u8)) -> Result<(), String> {
+    WebView::set_background_color(self, color).map_err(|e| e.to_string())
+  }
+  fn set_bounds(&self, bounds: Rect) -> Result<(), String> {
+    WebView::set_bounds(self, bounds).map_err(|e| e.to_string())
+  }
+  fn set_visible(&self, visible: bool) -> Result<(), String> {
+    WebView::set_visible(self, visible).map_err(|e| e.to_string())
+  }
+  #[cfg(target_os = "windows")]
+  fn bring_to_front(&self) -> Result<(), String> {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::{SetWindowPos, HWND_TOP, SWP_NOACTIVATE, SWP_NOMOVE, SWP_NOSIZE};
+    use wry::WebViewExtWindows;
+    unsafe {
+      let mut hwnd = HWND::default();
+      self.controller().ParentWindow(&mut hwnd).map_err(|e| e.to_string())?;
+      SetWindowPos(hwnd, Some(HWND_TOP), 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE).map_err(|e| e.to_string())
+    }
+  }
+  #[cfg(target_os = "macos")]
+  fn bring_to_front(&self) -> Result<(), String> {
+    use wry::WebViewExtMacOS;
+    // 重新添加到contentView会将webview移动到最上层
+    let ns_window = self.ns_window();
+    self.reparent(&*ns_window as *const _ as *mut _).map_err(|e| e.to_string())
+  }
+  #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+  fn bring_to_front(&self) -> Result<(), String> {
+    Err("当前平台不支持调整webview层级".to_string())
+  }
+}
+
+impl NativeTray for TrayIcon {
+  fn set_icon(&self, icon: Option<tray_icon::Icon>) -> Result<(), String> {
+    TrayIcon::set_icon(self, icon).map_err(|e| e.to_string())
+  }
+  fn set_tooltip(&self, tooltip: Option<&str>) -> Result<(), String> {
+    TrayIcon::set_tooltip(self, tooltip).map_err(|e| e.to_string())
+  }
+  fn set_title(&self, title: Option<&str>) {
+    TrayIcon::set_title(self, title)
+  }
+  fn set_menu(&self, menu: Option<Box<dyn ContextMenu>>) {
+    TrayIcon::set_menu(self, menu)
+  }
+  fn set_show_menu_on_left_click(&self, enable: bool) {
+    TrayIcon::set_show_menu_on_left_click(self, enable)
+  }
+  fn set_visible(&self, visible: bool) -> Result<(), String> {
+    TrayIcon::set_visible(self, visible).map_err(|e| e.to_string())
+  }
+}
+
+impl NativeClipboard for ArboardClipboard {
+  fn get_text(&mut self) -> Result<String, arboard::Error> {
+    ArboardClipboard::get_text(self)
+  }
+  fn set_text(&mut self, text: &str) -> Result<(), arboard::Error> {
+    ArboardClipboard::set_text(self, text)
+  }
+  fn get_html(&mut self) -> Result<String, arboard::Error> {
+    self.get().html()
+  }
+  fn set_html(&mut self, html: &str, alt_text: Option<&str>) -> Result<(), arboard::Error> {
+    ArboardClipboard::set_html(self, html, alt_text)
+  }
+  fn get_image(&mut self) -> Result<ImageData<'static>, arboard::Error> {
+    ArboardClipboard::get_image(self)
+  }
+  fn set_image(&mut self, image: ImageData<'static>) -> Result<(), arboard::Error> {
+    ArboardClipboard::set_image(self, image)
+  }
+  fn clear(&mut self) -> Result<(), arboard::Error> {
+    ArboardClipboard::clear(self)
+  }
+}
+
+impl NativeHotkeys for GlobalHotKeyManager {
+  fn register(&self, hotkey: HotKey) -> global_hotkey::Result<()> {
+    GlobalHotKeyManager::register(self, hotkey)
+  }
+  fn unregister(&self, hotkey: HotKey) -> global_hotkey::Result<()> {
+    GlobalHotKeyManager::unregister(self, hotkey)
+  }
+  fn unregister_all(&self, hotkeys: &[HotKey]) -> global_hotkey::Result<()> {
+    GlobalHotKeyManager::unregister_all(self, hotkeys)
+  }
+}
+
+impl Platform for ActiveEventLoop {
+  fn create_window(&self, window_attr: WindowAttributes) -> Result<Box<dyn NativeWindow>, String> {
+    let window = ActiveEventLoop::create_window(self, window_attr).map_err(|e| e.to_string())?;
+    Ok(Box::new(window))
+  }
+  fn available_monitors(&self) -> Vec<MonitorHandle> {
+    ActiveEventLoop::available_monitors(self).collect()
+  }
+  fn primary_monitor(&self) -> Option<MonitorHandle> {
+    ActiveEventLoop::primary_monitor(self)
+  }
+  fn set_custom_cursor(&self, window: &dyn NativeWindow, source: CustomCursorSource) {
+    window.set_cursor(self.create_custom_cursor(source).into());
+  }
+  fn exit(&self) {
+    ActiveEventLoop::exit(self)
+  }
+  fn create_tray(&self, id: &str, tray_attr: TrayIconAttributes) -> Result<Box<dyn NativeTray>, String> {
+    let tray = TrayIcon::with_id(id, tray_attr).map_err(|e| e.to_string())?;
+    Ok(Box::new(tray))
+  }
+  fn show_notification(&self, id: String, notification: Notification, response: Map<String, Value>) {
+    send_notification(id, notification, response);
+  }
+  fn register_url_scheme(&self, data: &Value) -> Result<(), String> {
+    register_url_scheme(data)
+  }
+  fn unregister_url_scheme(&self, data: &Value) -> Result<(), String> {
+    unregister_url_scheme(data)
+  }
+  fn show_dialog(&self, method: &str, data: &Value, response: Map<String, Value>) {
+    show_dialog(method, data, response, None)
+  }
+}
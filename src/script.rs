@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use serde_json::{Map, Value};
+use crate::window::Window;
+use crate::listen::{send_io_message, send_io_error};
+
+// evaluate的默认超时时间
+const EVALUATE_TIMEOUT: Duration = Duration::from_secs(30);
+
+static NEXT_SCRIPT_ID: AtomicU64 = AtomicU64::new(1);
+// 等待页面返回结果的脚本，key为窗口label和脚本id
+static PENDING_SCRIPTS: LazyLock<Mutex<HashMap<(String, u64), PendingScript>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+struct PendingScript {
+  // 写入包装脚本的随机值，页面返回的结果需要带上相同的值
+  nonce: String,
+  // 对应请求的响应
+  response: Map<String, Value>,
+  // 超过这个时间没有结果时以timeout_error作为错误响应
+  deadline: Instant,
+  timeout_error: String
+}
+
+// 包装要执行的函数体，同步结果通过evaluate_script_with_callback返回
+// 返回promise时等待完成后通过window.ipc.postMessage返回，结果需要带上随机值，防止页面伪造其他脚本的结果
+const SCRIPT_WRAPPER: &str = r#"
+(function () {
+  var id = __ID__;
+  var nonce = '__NONCE__';
+  var result = function (data) {
+    return { type: 'scriptResult', id: id, nonce: nonce, data: data === undefined ? null : data };
+  };
+  var error = function (e) {
+    var isError = e !== null && typeof e === 'object' && typeof e.message === 'string';
+    return { type: 'scriptResult', id: id, nonce: nonce, error: { message: isError ? e.message : String(e), stack: isError && e.stack ? String(e.stack) : null } };
+  };
+  var stringify = function (value) {
+    try { return JSON.stringify(value); } catch (e) { return JSON.stringify(error(e)); }
+  };
+  try {
+    var value = (function () {
+__BODY__
+    })();
+    if (value !== null && (typeof value === 'object' || typeof value === 'function') && typeof value.then === 'function') {
+      var post = function (message) { window.ipc.postMessage(stringify(message)); };
+      value.then(function (data) { post(result(data)); }, function (e) { post(error(e)); });
+      return null;
+    }
+    return stringify(result(value));
+  } catch (e) {
+    return stringify(error(e));
+  }
+})()
+"#;
+
+// 生成脚本的随机值，页面中的其他脚本无法猜到
+fn create_nonce() -> String {
+  let mut hasher = RandomState::new().build_hasher();
+  hasher.write_u64(NEXT_SCRIPT_ID.load(Ordering::Relaxed));
+  format!("{:016x}", hasher.finish())
+}
+
+// evaluate_script_with_callback返回的是执行结果的json，包装脚本的执行结果为json字符串，需要解析两次
+fn parse_callback_result(result: &str) -> Option<Value> {
+  match serde_json::from_str::<Value>(result).ok()? {
+    Value::String(json) => serde_json::from_str(&json).ok(),
+    _ => None
+  }
+}
+
+// 在页面中执行函数体，函数的返回值作为响应的数据，返回promise时等待promise完成
+// 超时或页面跳转导致没有结果时，以timeout_error作为错误响应，超时由事件循环通过expire_scripts检查
+pub fn run_script(window: &Window, body: &str, response: Map<String, Value>, timeout: Duration, timeout_error: String) {
+  let id = NEXT_SCRIPT_ID.fetch_add(1, Ordering::Relaxed);
+  let nonce = create_nonce();
+  let key = (window.label.clone(), id);
+  let script = SCRIPT_WRAPPER
+    .replace("__ID__", &id.to_string())
+    .replace("__NONCE__", &nonce)
+    .replace("__BODY__", body);
+  PENDING_SCRIPTS.lock().unwrap().insert(key.clone(), PendingScript { nonce, response, deadline: Instant::now() + timeout, timeout_error });
+  let label = window.label.clone();
+  let result = window.evaluate_script_with_callback(&script, move |result| {
+    if let Some(result) = parse_callback_result(&result) {
+      resolve_script(&label, None, &result);
+    }
+  });
+  // 脚本没有发送到页面时直接返回错误
+  if let Err(e) = result {
+    if let Some(pending) = PENDING_SCRIPTS.lock().unwrap().remove(&key) {
+      send_io_error(pending.response, e);
+    }
+  }
+}
+
+// 以超时错误响应已超时的脚本，返回下一个脚本超时的时间，事件循环需要在这个时间前唤醒
+pub fn expire_scripts() -> Option<Instant> {
+  let now = Instant::now();
+  let mut pending = PENDING_SCRIPTS.lock().unwrap();
+  let expired: Vec<(String, u64)> = pending.iter()
+    .filter(|(_, script)| script.deadline <= now)
+    .map(|(key, _)| key.clone())
+    .collect();
+  for key in expired {
+    let script = pending.remove(&key).unwrap();
+    send_io_error(script.response, script.timeout_error);
+  }
+  pending.values().map(|script| script.deadline).min()
+}
+
+// 获取超时时间(毫秒)，不设置时使用默认值，必须为大于0的整数
+pub fn get_timeout(data: &Value, default: Duration) -> Result<Duration, String> {
+  match data.get("timeout").filter(|v| !v.is_null()) {
+    None => Ok(default),
+    Some(timeout) => timeout.as_u64()
+      .filter(|timeout| *timeout > 0)
+      .map(Duration::from_millis)
+      .ok_or("timeout必须为大于0的整数".to_string())
+  }
+}
+
+// 处理页面返回的脚本结果，返回false表示不是等待中的脚本结果
+// 只接受脚本所在窗口的主webview返回的结果，webview为子webview的id
+// { type: 'scriptResult', id, nonce, data } 或 { type: 'scriptResult', id, nonce, error: { message, stack } }
+pub fn resolve_script(label: &str, webview: Option<&str>, message: &Value) -> bool {
+  if webview.is_some() || message.get("type").and_then(|v| v.as_str()) != Some("scriptResult") {
+    return false;
+  }
+  let Some(id) = message.get("id").and_then(|v| v.as_u64()) else {
+    return false;
+  };
+  let nonce = message.get("nonce").and_then(|v| v.as_str()).unwrap_or_default();
+  let key = (label.to_string(), id);
+  // 已超时或随机值不匹配的结果不处理
+  let mut pending = PENDING_SCRIPTS.lock().unwrap();
+  if pending.get(&key).is_none_or(|script| script.nonce != nonce) {
+    return false;
+  }
+  let mut response = pending.remove(&key).unwrap().response;
+  drop(pending);
+  match message.get("error") {
+    Some(error) => {
+      // 页面中的调用栈，node端设置为Error的stack
+      if let Some(stack) = error.get("stack").filter(|v| v.is_string()) {
+        response.insert("stack".to_string(), stack.clone());
+      }
+      let message = error.get("message").and_then(|v| v.as_str()).unwrap_or_default();
+      send_io_error(response, message.to_string());
+    },
+    None => {
+      response.insert("data".to_string(), message.get("data").cloned().unwrap_or(Value::Null));
+      send_io_message(Value::Object(response));
+    }
+  }
+  true
+}
+
+// 获取evaluate的脚本和超时时间
+// 参数为脚本字符串或 { script, timeout }
+pub fn get_evaluate_params(data: &Value) -> Result<(String, Duration), String> {
+  let script = match data {
+    Value::String(script) => script.as_str(),
+    _ => data.get("script").and_then(|v| v.as_str()).unwrap_or_default()
+  };
+  if script.is_empty() {
+    return Err("必须设置script".to_string());
+  }
+  Ok((script.to_string(), get_timeout(data, EVALUATE_TIMEOUT)?))
+}
+
+// 在页面的全局作用域执行脚本，返回最后一个表达式的值，值为promise时等待完成
+pub fn evaluate(window: &Window, data: &Value, response: Map<String, Value>) {
+  let (script, timeout) = match get_evaluate_params(data) {
+    Ok(params) => params,
+    Err(e) => {
+      send_io_error(response, e);
+      return;
+    }
+  };
+  // 间接eval在全局作用域执行，语法错误也会作为异常返回
+  let body = format!("      return (0, eval)({});", Value::String(script));
+  run_script(window, &body, response, timeout, format!("执行脚本超时({}ms)", timeout.as_millis()));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn evaluate_params_accept_string_or_object() {
+    assert_eq!(get_evaluate_params(&json!("1 + 1")), Ok(("1 + 1".to_string(), EVALUATE_TIMEOUT)));
+    assert_eq!(get_evaluate_params(&json!({ "script": "a", "timeout": 500 })), Ok(("a".to_string(), Duration::from_millis(500))));
+    assert_eq!(get_evaluate_params(&json!({ "timeout": 500 })), Err("必须设置script".to_string()));
+    assert_eq!(get_evaluate_params(&Value::Null), Err("必须设置script".to_string()));
+    assert_eq!(get_evaluate_params(&json!({ "script": "a", "timeout": 0 })), Err("timeout必须为大于0的整数".to_string()));
+    assert_eq!(get_evaluate_params(&json!({ "script": "a", "timeout": null })), Ok(("a".to_string(), EVALUATE_TIMEOUT)));
+  }
+
+  #[test]
+  fn callback_result_is_parsed_twice() {
+    let result = serde_json::to_string(&json!(r#"{"type":"scriptResult","id":1,"data":2}"#)).unwrap();
+    assert_eq!(parse_callback_result(&result), Some(json!({ "type": "scriptResult", "id": 1, "data": 2 })));
+    // 包装脚本返回promise时同步结果为null
+    assert_eq!(parse_callback_result("null"), None);
+  }
+
+  #[test]
+  fn results_are_only_accepted_from_the_target_window() {
+    use crate::listen::{record_io_messages, take_io_messages};
+    let id = NEXT_SCRIPT_ID.fetch_add(1, Ordering::Relaxed);
+    let nonce = create_nonce();
+    let response = Map::from_iter([("id".to_string(), json!("1"))]);
+    PENDING_SCRIPTS.lock().unwrap().insert(("main".to_string(), id), PendingScript { nonce: nonce.clone(), response, deadline: Instant::now() + EVALUATE_TIMEOUT, timeout_error: String::new() });
+    record_io_messages();
+    let result = json!({ "type": "scriptResult", "id": id, "nonce": nonce, "data": 1 });
+    assert!(!resolve_script("other", None, &result));
+    assert!(!resolve_script("main", Some("child"), &result));
+    assert!(!resolve_script("main", None, &json!({ "type": "scriptResult", "id": id, "nonce": "guess", "data": 2 })));
+    assert!(resolve_script("main", None, &result));
+    assert!(!resolve_script("main", None, &result));
+    assert_eq!(take_io_messages(), vec![json!({ "id": "1", "data": 1 })]);
+  }
+
+  #[test]
+  fn expired_scripts_reply_with_timeout_error() {
+    use crate::listen::{record_io_messages, take_io_messages};
+    let id = NEXT_SCRIPT_ID.fetch_add(1, Ordering::Relaxed);
+    let response = Map::from_iter([("id".to_string(), json!("2"))]);
+    let deadline = Instant::now();
+    PENDING_SCRIPTS.lock().unwrap().insert(("main".to_string(), id), PendingScript { nonce: create_nonce(), response, deadline, timeout_error: "执行脚本超时".to_string() });
+    record_io_messages();
+    assert!(expire_scripts().is_none_or(|next| next > deadline));
+    assert_eq!(take_io_messages(), vec![json!({ "id": "2", "error": "执行脚本超时" })]);
+    assert!(!PENDING_SCRIPTS.lock().unwrap().contains_key(&("main".to_string(), id)));
+  }
+}
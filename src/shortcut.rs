@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use serde_json::{Map, Value};
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+use global_hotkey::hotkey::HotKey;
+use crate::event::send_app_event;
+use crate::platform::NativeHotkeys;
+
+// 以快捷键内部id为key的已注册快捷键，事件回调中只能拿到内部id
+static SHORTCUT_IDS: LazyLock<Mutex<HashMap<u32, RegisteredShortcut>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Clone)]
+struct RegisteredShortcut {
+  id: String,
+  accelerator: String,
+  // 是否发送松开按键的事件，默认只发送按下的事件
+  release_event: bool
+}
+
+// 获取注册快捷键的参数，未设置id时使用快捷键字符串作为id
+// { id, accelerator, releaseEvent }
+pub fn get_shortcut_params(data: &Value) -> Result<(String, String, bool), String> {
+  let accelerator = data.get("accelerator").and_then(|v| v.as_str()).ok_or("必须设置accelerator")?;
+  parse_hotkey(accelerator)?;
+  let id = data.get("id").and_then(|v| v.as_str()).unwrap_or(accelerator).to_string();
+  let release_event = data.get("releaseEvent").and_then(|v| v.as_bool()).unwrap_or(false);
+  Ok((id, accelerator.to_string(), release_event))
+}
+
+fn parse_hotkey(accelerator: &str) -> Result<HotKey, String> {
+  accelerator.parse().map_err(|e| format!("无效的快捷键 {}: {}", accelerator, e))
+}
+
+// 全局快捷键，在应用没有焦点时也能触发
+#[derive(Default)]
+pub struct Shortcuts {
+  manager: Option<Box<dyn NativeHotkeys>>,
+  // 以快捷键id为key的已注册快捷键
+  hotkeys: HashMap<String, HotKey>
+}
+
+impl Shortcuts {
+  // 使用指定的快捷键管理器，如模拟模式中的内存实现
+  pub fn with_manager(manager: Box<dyn NativeHotkeys>) -> Self {
+    Self { manager: Some(manager), hotkeys: HashMap::new() }
+  }
+  // 第一次注册快捷键时才创建，需要在事件循环所在的线程创建
+  fn manager(&mut self) -> Result<&dyn NativeHotkeys, String> {
+    if self.manager.is_none() {
+      self.manager = Some(Box::new(GlobalHotKeyManager::new().map_err(|e| e.to_string())?));
+    }
+    Ok(self.manager.as_deref().unwrap())
+  }
+  // 以快捷键id为key的已注册快捷键
+  pub fn hotkeys(&self) -> &HashMap<String, HotKey> {
+    &self.hotkeys
+  }
+  // 注册快捷键，快捷键已被其他程序占用时返回错误
+  pub fn register(&mut self, id: String, accelerator: &str, release_event: bool) -> Result<(), String> {
+    if self.hotkeys.contains_key(&id) {
+      return Err(format!("快捷键 {} 已注册", id));
+    }
+    let hotkey = parse_hotkey(accelerator)?;
+    self.manager()?.register(hotkey).map_err(|e| e.to_string())?;
+    let shortcut = RegisteredShortcut { id: id.clone(), accelerator: accelerator.to_string(), release_event };
+    SHORTCUT_IDS.lock().unwrap().insert(hotkey.id(), shortcut);
+    self.hotkeys.insert(id, hotkey);
+    Ok(())
+  }
+  // 注销快捷键
+  pub fn unregister(&mut self, id: &str) -> Result<(), String> {
+    let hotkey = self.hotkeys.remove(id).ok_or(format!("快捷键 {} 未注册", id))?;
+    SHORTCUT_IDS.lock().unwrap().remove(&hotkey.id());
+    self.manager()?.unregister(hotkey).map_err(|e| e.to_string())
+  }
+  // 注销所有快捷键
+  pub fn unregister_all(&mut self) -> Result<(), String> {
+    let hotkeys: Vec<HotKey> = self.hotkeys.drain().map(|(_, hotkey)| hotkey).collect();
+    SHORTCUT_IDS.lock().unwrap().clear();
+    self.manager()?.unregister_all(&hotkeys).map_err(|e| e.to_string())
+  }
+}
+
+// 快捷键事件转发给node端，松开按键的事件只在注册时设置了releaseEvent才发送
+pub fn listen_shortcut_events() {
+  GlobalHotKeyEvent::set_event_handler(Some(|event: GlobalHotKeyEvent| {
+    let Some(shortcut) = SHORTCUT_IDS.lock().unwrap().get(&event.id()).cloned() else {
+      return;
+    };
+    let state = match event.state() {
+      HotKeyState::Pressed => "pressed",
+      HotKeyState::Released if shortcut.release_event => "released",
+      HotKeyState::Released => return
+    };
+    let mut data = Map::new();
+    data.insert("id".to_string(), Value::String(shortcut.id));
+    data.insert("accelerator".to_string(), Value::String(shortcut.accelerator));
+    data.insert("state".to_string(), Value::String(state.to_string()));
+    send_app_event("globalShortcut", Some(Value::Object(data)));
+  }));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  #[test]
+  fn shortcut_params_default_id_to_accelerator() {
+    let params = get_shortcut_params(&json!({ "accelerator": "CmdOrCtrl+Shift+K" }));
+    assert_eq!(params, Ok(("CmdOrCtrl+Shift+K".to_string(), "CmdOrCtrl+Shift+K".to_string(), false)));
+    let params = get_shortcut_params(&json!({ "id": "search", "accelerator": "Alt+Space", "releaseEvent": true }));
+    assert_eq!(params, Ok(("search".to_string(), "Alt+Space".to_string(), true)));
+    assert_eq!(get_shortcut_params(&json!({})), Err("必须设置accelerator".to_string()));
+    assert!(get_shortcut_params(&json!({ "accelerator": "Ctrl+Nope" })).unwrap_err().starts_with("无效的快捷键 Ctrl+Nope"));
+  }
+}
@@ -0,0 +1,137 @@
+use std::env;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::thread;
+use std::time::Duration;
+use serde_json::{json, Value};
+use interprocess::local_socket::{prelude::*, GenericNamespaced, Listener, ListenerOptions, Name, Stream};
+use crate::event::send_app_event;
+use crate::deep_link::{get_urls, send_open_urls};
+
+// 已有实例在运行时，参数转发后以该退出码退出，node端收到后同样退出
+pub const SECOND_INSTANCE_EXIT_CODE: i32 = 2;
+
+// 读取后启动的实例转发的参数的超时时间
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 启动参数
+// --single-instance <id> --url-scheme <scheme> -- [应用的启动参数...]
+struct LaunchArgs {
+  id: Option<String>,
+  // 应用处理的url协议，可以设置多个
+  url_schemes: Vec<String>,
+  args: Vec<String>
+}
+
+fn parse_args() -> LaunchArgs {
+  let mut id = None;
+  let mut url_schemes = Vec::new();
+  let mut args = Vec::new();
+  let mut iter = env::args().skip(1);
+  while let Some(arg) = iter.next() {
+    match arg.as_str() {
+      "--single-instance" => id = iter.next(),
+      "--url-scheme" => url_schemes.extend(iter.next()),
+      "--" => {
+        args.extend(iter.by_ref());
+      },
+      _ => {}
+    }
+  }
+  LaunchArgs { id, url_schemes, args }
+}
+
+// 同一应用id的实例使用同一个本地socket，Windows上为命名管道
+fn get_name(id: &str) -> std::io::Result<Name<'static>> {
+  format!("node-winit-wry-{}.sock", id).to_ns_name::<GenericNamespaced>()
+}
+
+// 获取启动参数和当前工作目录
+fn get_instance_data(args: &[String]) -> Value {
+  let cwd = env::current_dir().map(|cwd| cwd.to_string_lossy().to_string()).unwrap_or_default();
+  json!({ "args": args, "cwd": cwd })
+}
+
+// 把参数和工作目录转发给已运行的实例，没有已运行的实例时返回false
+fn forward_to_running(name: Name<'static>, data: &Value) -> bool {
+  let Ok(mut stream) = Stream::connect(name) else {
+    return false;
+  };
+  stream.write_all(format!("{}\n", data).as_bytes()).is_ok()
+}
+
+// 创建监听，socket已被占用(已有实例在运行)时返回AddrInUse
+// Windows上命名管道已存在时返回PermissionDenied
+fn create_listener(name: Name<'static>, overwrite: bool) -> io::Result<Listener> {
+  ListenerOptions::new().name(name).try_overwrite(overwrite).create_sync().map_err(|e| {
+    match e.kind() {
+      ErrorKind::PermissionDenied if cfg!(target_os = "windows") => io::Error::new(ErrorKind::AddrInUse, e),
+      _ => e
+    }
+  })
+}
+
+// 读取一个实例转发的参数，作为secondInstance事件发送给node端，参数中的url作为openUrl事件发送
+fn handle_second_instance(stream: Stream, url_schemes: &[String]) {
+  // 连接后不发送数据的客户端不能一直占用线程
+  if stream.set_recv_timeout(Some(READ_TIMEOUT)).is_err() {
+    return;
+  }
+  let mut line = String::new();
+  if BufReader::new(stream).read_line(&mut line).is_err() {
+    return;
+  }
+  let Ok(data) = serde_json::from_str::<Value>(&line) else {
+    return;
+  };
+  let args: Vec<String> = data.get("args")
+    .and_then(|v| v.as_array())
+    .map(|args| args.iter().filter_map(|arg| arg.as_str()).map(|arg| arg.to_string()).collect())
+    .unwrap_or_default();
+  send_app_event("secondInstance", Some(data));
+  send_open_urls(get_urls(&args, url_schemes));
+}
+
+// 监听后启动的实例转发的参数，每个连接在单独的线程中读取
+fn listen_second_instance(listener: Listener, url_schemes: Vec<String>) {
+  thread::spawn(move || {
+    for stream in listener.incoming().filter_map(|stream| stream.ok()) {
+      let url_schemes = url_schemes.clone();
+      thread::spawn(move || handle_second_instance(stream, &url_schemes));
+    }
+  });
+}
+
+// 启动时获取单实例锁，已有实例在运行时把参数转发给该实例并返回false
+// 获取到锁或未启用单实例时，启动参数中的url作为openUrl事件发送
+pub fn init() -> bool {
+  let LaunchArgs { id, url_schemes, args } = parse_args();
+  let Some(id) = id else {
+    send_open_urls(get_urls(&args, &url_schemes));
+    return true;
+  };
+  let name = match get_name(&id) {
+    Ok(name) => name,
+    Err(e) => {
+      eprintln!("无效的单实例id {}: {}", id, e);
+      send_open_urls(get_urls(&args, &url_schemes));
+      return true;
+    }
+  };
+  // 先创建监听作为锁，同时启动的实例只有一个能创建成功
+  let listener = match create_listener(name.clone(), false) {
+    Err(e) if e.kind() == ErrorKind::AddrInUse => {
+      if forward_to_running(name.clone(), &get_instance_data(&args)) {
+        return false;
+      }
+      // 无法连接说明是上一个实例异常退出时残留的socket文件，覆盖后作为第一个实例
+      create_listener(name, true)
+    },
+    result => result
+  };
+  send_open_urls(get_urls(&args, &url_schemes));
+  match listener {
+    Ok(listener) => listen_second_instance(listener, url_schemes),
+    Err(e) => eprintln!("获取单实例锁错误: {}", e)
+  }
+  true
+}
@@ -0,0 +1,113 @@
+use std::fs;
+use std::path::Path;
+use serde_json::{Map, Value};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::window::{Fullscreen, WindowAttributes};
+use crate::window::Window;
+use crate::platform::Platform;
+
+// 窗口至少要有这么多像素在显示器内才认为位置有效
+const MIN_VISIBLE_SIZE: i32 = 50;
+
+// 窗口的位置、尺寸等状态(物理像素)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowState {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+  pub maximized: bool,
+  pub fullscreen: bool
+}
+
+impl WindowState {
+  pub fn from_value(value: &Value) -> Option<Self> {
+    let obj = value.as_object()?;
+    Some(Self {
+      x: obj.get("x")?.as_i64()? as i32,
+      y: obj.get("y")?.as_i64()? as i32,
+      width: obj.get("width")?.as_u64()? as u32,
+      height: obj.get("height")?.as_u64()? as u32,
+      maximized: obj.get("maximized").and_then(|v| v.as_bool()).unwrap_or(false),
+      fullscreen: obj.get("fullscreen").and_then(|v| v.as_bool()).unwrap_or(false)
+    })
+  }
+  pub fn to_value(&self) -> Value {
+    let mut data = Map::new();
+    data.insert("x".to_string(), self.x.into());
+    data.insert("y".to_string(), self.y.into());
+    data.insert("width".to_string(), self.width.into());
+    data.insert("height".to_string(), self.height.into());
+    data.insert("maximized".to_string(), Value::Bool(self.maximized));
+    data.insert("fullscreen".to_string(), Value::Bool(self.fullscreen));
+    Value::Object(data)
+  }
+  // 从文件读取窗口状态
+  pub fn load(path: &Path) -> Option<Self> {
+    let content = fs::read_to_string(path).ok()?;
+    let value: Value = serde_json::from_str(&content).ok()?;
+    Self::from_value(&value)
+  }
+  // 将窗口状态写入文件
+  pub fn save(&self, path: &Path) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(&self.to_value()).unwrap())
+  }
+  // 获取窗口当前状态，最大化或全屏时保留之前的位置和尺寸，以便还原后回到原来的位置
+  pub fn capture(window: &Window, previous: Option<Self>) -> Option<Self> {
+    if window.is_minimized() == Some(true) {
+      return None;
+    }
+    let maximized = window.is_maximized();
+    let fullscreen = window.fullscreen().is_some();
+    if let (true, Some(previous)) = (maximized || fullscreen, previous) {
+      return Some(Self { maximized, fullscreen, ..previous });
+    }
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size();
+    Some(Self {
+      x: position.x,
+      y: position.y,
+      width: size.width,
+      height: size.height,
+      maximized,
+      fullscreen
+    })
+  }
+  // 窗口是否有足够的区域位于某个显示器内
+  fn is_visible_on(&self, platform: &dyn Platform) -> bool {
+    let monitors = platform.available_monitors();
+    // 无法获取显示器信息时(如Wayland)不做校验
+    if monitors.is_empty() {
+      return true;
+    }
+    monitors.iter().any(|monitor| {
+      let position = monitor.position();
+      let size = monitor.size();
+      let left = self.x.max(position.x);
+      let top = self.y.max(position.y);
+      let right = (self.x + self.width as i32).min(position.x + size.width as i32);
+      let bottom = (self.y + self.height as i32).min(position.y + size.height as i32);
+      right - left >= MIN_VISIBLE_SIZE && bottom - top >= MIN_VISIBLE_SIZE
+    })
+  }
+  // 将窗口状态应用到窗口属性，位置不在任何显示器内时只还原尺寸
+  pub fn apply(&self, mut window_attr: WindowAttributes, platform: &dyn Platform) -> WindowAttributes {
+    let mut size = PhysicalSize::new(self.width, self.height);
+    if self.is_visible_on(platform) {
+      window_attr = window_attr.with_position(PhysicalPosition::new(self.x, self.y));
+    }else if let Some(monitor) = platform.primary_monitor() {
+      // 之前所在的显示器已经不存在，尺寸不能超过主显示器
+      size.width = size.width.min(monitor.size().width);
+      size.height = size.height.min(monitor.size().height);
+    }
+    window_attr = window_attr.with_inner_size(size);
+    window_attr = window_attr.with_maximized(self.maximized);
+    if self.fullscreen {
+      window_attr = window_attr.with_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+    window_attr
+  }
+}
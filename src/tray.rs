@@ -0,0 +1,110 @@
+use serde_json::{Map, Value};
+use tray_icon::{Icon, MouseButton, MouseButtonState, TrayIconAttributes, TrayIconEvent};
+use crate::event::send_app_event;
+use muda::{ContextMenu, Menu};
+use crate::menu::{build_menu, MenuItems};
+use crate::window::load_rgba_image;
+use crate::platform::{NativeTray, Platform};
+
+// 读取托盘图标
+fn get_tray_icon(path: &str) -> Result<Icon, String> {
+  let (rgba, width, height) = load_rgba_image(path)?;
+  Icon::from_rgba(rgba, width, height).map_err(|e| e.to_string())
+}
+
+// 托盘图标和它的菜单，菜单被替换或移除时需要清理其中的菜单项
+pub struct Tray {
+  pub icon: Box<dyn NativeTray>,
+  pub menu: Option<Menu>
+}
+
+// 创建托盘图标
+// { id, icon, tooltip, title, menu, menuOnLeftClick }
+pub fn create_tray(platform: &dyn Platform, id: &str, attr: &Map<String, Value>, menu_items: &mut MenuItems) -> Result<Tray, String> {
+  let mut tray_attr = TrayIconAttributes::default();
+  if let Some(icon) = attr.get("icon").and_then(|v| v.as_str()) {
+    tray_attr.icon = Some(get_tray_icon(icon)?);
+  }
+  if let Some(tooltip) = attr.get("tooltip").and_then(|v| v.as_str()) {
+    tray_attr.tooltip = Some(tooltip.to_string());
+  }
+  if let Some(title) = attr.get("title").and_then(|v| v.as_str()) {
+    tray_attr.title = Some(title.to_string());
+  }
+  let menu = match attr.get("menu") {
+    Some(Value::Null) | None => None,
+    Some(menu) => Some(build_menu(menu, menu_items)?)
+  };
+  if let Some(menu) = &menu {
+    tray_attr.menu = Some(Box::new(menu.clone()));
+  }
+  if let Some(menu_on_left_click) = attr.get("menuOnLeftClick").and_then(|v| v.as_bool()) {
+    tray_attr.menu_on_left_click = menu_on_left_click;
+  }
+  let icon = platform.create_tray(id, tray_attr)?;
+  Ok(Tray { icon, menu })
+}
+
+// 修改托盘图标，值为null时清除对应的属性
+pub fn update_tray(tray: &mut Tray, attr: &Map<String, Value>, menu_items: &mut MenuItems) -> Result<(), String> {
+  for (key, value) in attr {
+    match key.as_str() {
+      "icon" => {
+        let icon = value.as_str().map(get_tray_icon).transpose()?;
+        tray.icon.set_icon(icon)?;
+      },
+      "tooltip" => {
+        tray.icon.set_tooltip(value.as_str())?;
+      },
+      "title" => {
+        tray.icon.set_title(value.as_str());
+      },
+      "menu" => {
+        let menu = match value {
+          Value::Null => None,
+          menu => Some(build_menu(menu, menu_items)?)
+        };
+        tray.icon.set_menu(menu.clone().map(|menu| Box::new(menu) as Box<dyn ContextMenu>));
+        tray.menu = menu;
+      },
+      "menuOnLeftClick" => {
+        if let Some(menu_on_left_click) = value.as_bool() {
+          tray.icon.set_show_menu_on_left_click(menu_on_left_click);
+        }
+      },
+      "visible" => {
+        if let Some(visible) = value.as_bool() {
+          tray.icon.set_visible(visible)?;
+        }
+      },
+      _ => {}
+    }
+  }
+  Ok(())
+}
+
+fn get_button_name(button: MouseButton) -> &'static str {
+  match button {
+    MouseButton::Left => "left",
+    MouseButton::Right => "right",
+    MouseButton::Middle => "middle"
+  }
+}
+
+// 托盘图标的点击事件转发给node端
+pub fn listen_tray_events() {
+  TrayIconEvent::set_event_handler(Some(|event: TrayIconEvent| {
+    let (method, id, position, button) = match event {
+      // 只在松开按键时触发点击事件
+      TrayIconEvent::Click { id, position, button, button_state: MouseButtonState::Up, .. } => ("trayClick", id, position, button),
+      TrayIconEvent::DoubleClick { id, position, button, .. } => ("trayDoubleClick", id, position, button),
+      _ => return
+    };
+    let mut data = Map::new();
+    data.insert("id".to_string(), Value::String(id.0));
+    data.insert("x".to_string(), position.x.into());
+    data.insert("y".to_string(), position.y.into());
+    data.insert("button".to_string(), Value::String(get_button_name(button).to_string()));
+    send_app_event(method, Some(Value::Object(data)));
+  }));
+}
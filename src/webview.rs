@@ -0,0 +1,204 @@
+use std::sync::Arc;
+#[cfg(any(target_os = "macos", test))]
+use std::path::Path;
+use serde_json::{Map, Value};
+use winit::dpi::{LogicalPosition, LogicalSize};
+use wry::Rect;
+use crate::platform::NativeWebview;
+
+// 长度，数字为逻辑像素，"50%"形式的字符串为相对窗口尺寸的百分比
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Length {
+  Pixel(f64),
+  Percent(f64)
+}
+
+impl Length {
+  pub fn from_value(value: &Value) -> Result<Option<Length>, String> {
+    match value {
+      Value::Null => Ok(None),
+      Value::Number(n) => Ok(Some(Length::Pixel(n.as_f64().unwrap()))),
+      Value::String(s) => {
+        let percent = s.trim().strip_suffix('%').ok_or(format!("无效的长度: {}", s))?;
+        let percent:f64 = percent.trim().parse().map_err(|_| format!("无效的长度: {}", s))?;
+        Ok(Some(Length::Percent(percent)))
+      },
+      _ => Err(format!("无效的长度: {}", value))
+    }
+  }
+  // 根据总长度计算实际的逻辑像素
+  pub fn resolve(&self, total: f64) -> f64 {
+    match self {
+      Length::Pixel(v) => *v,
+      Length::Percent(p) => total * p / 100.0
+    }
+  }
+}
+
+// webview布局，类似css的绝对定位
+// x/y为距离左/上边的距离，right/bottom为距离右/下边的距离，宽高未设置时由两边的距离撑开
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Layout {
+  pub x: Option<Length>,
+  pub y: Option<Length>,
+  pub width: Option<Length>,
+  pub height: Option<Length>,
+  pub right: Option<Length>,
+  pub bottom: Option<Length>
+}
+
+// 计算一个方向上的起点和长度
+fn resolve_axis(start: Option<Length>, length: Option<Length>, end: Option<Length>, total: f64) -> (f64, f64) {
+  let start = start.map(|v| v.resolve(total));
+  let end = end.map(|v| v.resolve(total));
+  match length.map(|v| v.resolve(total)) {
+    Some(length) => match (start, end) {
+      (None, Some(end)) => (total - end - length, length),
+      (start, _) => (start.unwrap_or(0.0), length)
+    },
+    None => {
+      let start = start.unwrap_or(0.0);
+      (start, (total - start - end.unwrap_or(0.0)).max(0.0))
+    }
+  }
+}
+
+impl Layout {
+  // 占满整个窗口的布局
+  pub fn fill() -> Self {
+    Self::default()
+  }
+  pub fn from_value(obj: &Value) -> Result<Layout, String> {
+    let attr = obj.as_object().ok_or("bounds必须为对象")?;
+    let get = |key: &str| Length::from_value(attr.get(key).unwrap_or(&Value::Null));
+    Ok(Layout {
+      x: get("x")?,
+      y: get("y")?,
+      width: get("width")?,
+      height: get("height")?,
+      right: get("right")?,
+      bottom: get("bottom")?
+    })
+  }
+  // 根据窗口的逻辑尺寸计算webview的位置和尺寸
+  pub fn resolve(&self, size: LogicalSize<f64>) -> (LogicalPosition<f64>, LogicalSize<f64>) {
+    let (x, width) = resolve_axis(self.x, self.width, self.right, size.width);
+    let (y, height) = resolve_axis(self.y, self.height, self.bottom, size.height);
+    (LogicalPosition::new(x, y), LogicalSize::new(width, height))
+  }
+  pub fn to_rect(&self, size: LogicalSize<f64>) -> Rect {
+    let (position, size) = self.resolve(size);
+    Rect {
+      position: position.into(),
+      size: size.into()
+    }
+  }
+}
+
+// 计算调整层级后的顺序，返回按从下到上排列的原下标
+// ids按从下到上排列，未列出的webview保持原有顺序并位于下方
+pub fn reorder_indices(current: &[&str], ids: &[String]) -> Result<Vec<usize>, String> {
+  if let Some(id) = ids.iter().find(|id| !current.contains(&id.as_str())) {
+    return Err(format!("webview {} 不存在", id));
+  }
+  if let Some((_, id)) = ids.iter().enumerate().find(|(i, id)| ids[..*i].contains(id)) {
+    return Err(format!("webview {} 重复", id));
+  }
+  let mut order: Vec<usize> = (0..current.len()).filter(|i| !ids.iter().any(|id| id == current[*i])).collect();
+  order.extend(ids.iter().filter_map(|id| current.iter().position(|c| c == id)));
+  Ok(order)
+}
+
+// 按reorder_indices返回的顺序重新排列
+pub fn apply_order<T>(items: &mut Vec<T>, order: &[usize]) {
+  let mut taken: Vec<Option<T>> = items.drain(..).map(Some).collect();
+  items.extend(order.iter().filter_map(|i| taken[*i].take()));
+}
+
+// macOS的WKWebView不支持数据目录，按数据目录的路径生成固定的数据存储标识(FNV-1a 128位哈希)
+// 同一路径每次启动得到相同的标识，不同路径的窗口使用独立的cookie、localStorage和缓存
+#[cfg(any(target_os = "macos", test))]
+pub fn data_store_identifier(path: &Path) -> [u8; 16] {
+  let mut hash: u128 = 0x6c62272e07bb014262b821756295c58d;
+  for byte in path.to_string_lossy().as_bytes() {
+    hash ^= *byte as u128;
+    hash = hash.wrapping_mul(0x0000000001000000000000000000013b);
+  }
+  hash.to_be_bytes()
+}
+
+// 窗口内的子webview
+#[derive(Clone)]
+pub struct ChildWebview {
+  pub id: String,
+  pub webview: Arc<dyn NativeWebview>,
+  pub layout: Layout,
+  pub visible: bool
+}
+
+impl ChildWebview {
+  pub fn new(id: String, webview: Box<dyn NativeWebview>, layout: Layout, visible: bool) -> Self {
+    Self {
+      id,
+      webview: Arc::from(webview),
+      layout,
+      visible
+    }
+  }
+  // 按窗口的逻辑尺寸重新布局
+  pub fn apply_layout(&self, size: LogicalSize<f64>) {
+    let _ = self.webview.set_bounds(self.layout.to_rect(size));
+  }
+  pub fn set_visible(&mut self, visible: bool) {
+    self.visible = visible;
+    let _ = self.webview.set_visible(visible);
+  }
+  // 获取webview信息
+  pub fn info(&self, size: LogicalSize<f64>) -> Value {
+    let (position, size) = self.layout.resolve(size);
+    let mut data = Map::new();
+    data.insert("id".to_string(), Value::String(self.id.clone()));
+    data.insert("x".to_string(), position.x.into());
+    data.insert("y".to_string(), position.y.into());
+    data.insert("width".to_string(), size.width.into());
+    data.insert("height".to_string(), size.height.into());
+    data.insert("visible".to_string(), Value::Bool(self.visible));
+    data.insert("url".to_string(), self.webview.url().map_or(Value::Null, Value::String));
+    Value::Object(data)
+  }
+  // 将webview移动到窗口内所有webview的最上层
+  pub fn bring_to_front(&self) -> Result<(), String> {
+    self.webview.bring_to_front()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn data_store_identifier_is_stable_per_directory() {
+    let a = data_store_identifier(Path::new("/data/a"));
+    assert_eq!(a, data_store_identifier(Path::new("/data/a")));
+    assert_ne!(a, data_store_identifier(Path::new("/data/b")));
+  }
+
+  fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+  }
+
+  #[test]
+  fn reorder_keeps_unlisted_below() {
+    let mut webviews = vec!["a", "b", "c", "d"];
+    let order = reorder_indices(&webviews, &ids(&["a", "c"])).unwrap();
+    apply_order(&mut webviews, &order);
+    assert_eq!(webviews, vec!["b", "d", "a", "c"]);
+  }
+
+  #[test]
+  fn reorder_rejects_missing_and_duplicate_ids() {
+    let webviews = vec!["a", "b"];
+    assert_eq!(reorder_indices(&webviews, &ids(&["c"])), Err("webview c 不存在".to_string()));
+    assert_eq!(reorder_indices(&webviews, &ids(&["a", "b", "a"])), Err("webview a 重复".to_string()));
+  }
+}
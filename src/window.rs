@@ -1,33 +1,88 @@
-use std::sync::Arc;
-use winit::window::{Window as WinitWindow, WindowId, WindowButtons, Fullscreen, WindowLevel, UserAttentionType, Theme, ResizeDirection};
-use wry::WebView;
-use wry::dpi::LogicalPosition;
-use wry::Rect;
-use winit::dpi::{PhysicalPosition, PhysicalSize, Position, Size};
-use winit::error::NotSupportedError;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use winit::window::{Cursor, CursorGrabMode, ImePurpose, WindowAttributes, WindowId, WindowButtons, Fullscreen, WindowLevel, UserAttentionType, Theme, ResizeDirection};
+use wry::cookie::Cookie;
+use wry::http::HeaderMap;
+use crate::webview::{ChildWebview, Layout, reorder_indices, apply_order};
+use crate::state::WindowState;
+use crate::hittest::HittestRegion;
+use crate::platform::{NativeWindow, NativeWebview};
+use muda::Menu;
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Position, Size};
+use winit::error::{ExternalError, NotSupportedError};
 use winit::monitor::MonitorHandle;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use image::GenericImageView;
 
+// 移动和缩放停止后写入窗口状态的延迟
+const STATE_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+#[cfg(not(any(debug_assertions, feature = "devtools")))]
+const DEVTOOLS_UNSUPPORTED: &str = "当前构建未开启devtools特性，不支持调试工具";
+
+// 读取图片文件，返回rgba数据和宽高
+pub fn load_rgba_image(path: &str) -> Result<(Vec<u8>, u32, u32), String> {
+  let image = image::open(Path::new(path)).map_err(|e| format!("读取图片 {} 错误: {}", path, e))?;
+  let (width, height) = image.dimensions();
+  Ok((image.to_rgba8().into_raw(), width, height))
+}
+
 #[derive(Clone)]
 pub struct Window {
   pub label: String,
-  pub window: Arc<WinitWindow>,
-  pub webview: Arc<WebView>,
+  pub window: Arc<dyn NativeWindow>,
+  pub webview: Arc<dyn NativeWebview>,
+  // 主webview的布局，窗口尺寸变化时按布局重新计算位置和尺寸
+  pub layout: Layout,
+  pub webview_visible: bool,
+  // 子webview，按层级从下到上排列
+  pub children: Vec<ChildWebview>,
+  // 父窗口的label，父窗口关闭时一起关闭
+  pub parent: Option<String>,
+  // 是否是父窗口的模态窗口
+  pub modal: bool,
+  // 保存窗口位置、尺寸等状态的文件
+  pub state_file: Option<PathBuf>,
+  // 内存中的窗口状态，移动和缩放时更新，关闭时写入状态文件
+  state: Arc<Mutex<Option<WindowState>>>,
+  // 窗口状态变化后等待写入文件的时间，移动和缩放停止一段时间后才写入
+  state_save_at: Arc<Mutex<Option<Instant>>>,
+  // 窗口是否接收鼠标事件，为false时鼠标事件会穿透到下方的窗口
+  pub cursor_hittest: bool,
+  // 页面设置的可点击区域，光标不在区域内时鼠标穿透
+  pub hittest_regions: Option<Vec<HittestRegion>>,
+  // 是否允许页面通过ipc设置鼠标穿透和可点击区域(创建时的hittestRegions选项)
+  pub page_hittest: bool,
+  // 窗口单独设置的菜单栏
+  pub menu: Option<Menu>,
   id: WindowId
 }
 
 impl Window{
   pub fn new (
     label: String,
-    window:WinitWindow,
-    webview: WebView,
-    id: WindowId
+    window: Box<dyn NativeWindow>,
+    webview: Box<dyn NativeWebview>,
+    id: WindowId,
+    layout: Layout,
+    webview_visible: bool
   ) -> Self {
     Self {
       label,
-      window: Arc::new(window),
-      webview: Arc::new(webview),
+      window: Arc::from(window),
+      webview: Arc::from(webview),
+      layout,
+      webview_visible,
+      children: Vec::new(),
+      parent: None,
+      modal: false,
+      state_file: None,
+      state: Arc::new(Mutex::new(None)),
+      state_save_at: Arc::new(Mutex::new(None)),
+      cursor_hittest: true,
+      hittest_regions: None,
+      page_hittest: false,
+      menu: None,
       id
     }
   }
@@ -38,6 +93,10 @@ impl Window{
   pub fn set_url(&self, url: String) {
     let _ = self.webview.load_url(&url);
   }
+  // 使用自定义请求头加载url
+  pub fn load_url_with_headers(&self, url: String, headers: HeaderMap) {
+    let _ = self.webview.load_url_with_headers(&url, headers);
+  }
   // 获取webview的url
   pub fn url(&self) -> String {
     self.webview.url().unwrap()
@@ -51,21 +110,38 @@ impl Window{
     &self,
     js: &str,
     callback: impl Fn(String) + Send + 'static,
-  ) {
-    let _ = self.webview.evaluate_script_with_callback(js, callback);
-  }
-  // 打开调试工具(打成正式包没有这个方法)
-  // pub fn open_devtools(&self) {
-  //   self.webview.open_devtools();
-  // }
-  // // 关闭调试工具
-  // pub fn close_devtools(&self) {
-  //   self.webview.close_devtools();
-  // }
-  // // 调试工具是否打开
-  // pub fn is_devtools_open(&self) -> bool {
-  //   self.webview.is_devtools_open()
-  // }
+  ) -> Result<(), String> {
+    self.webview.evaluate_script_with_callback(js, Box::new(callback))
+  }
+  // 打开调试工具(正式包需要开启devtools特性)
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn open_devtools(&self) -> Result<(), String> {
+    self.webview.open_devtools();
+    Ok(())
+  }
+  #[cfg(not(any(debug_assertions, feature = "devtools")))]
+  pub fn open_devtools(&self) -> Result<(), String> {
+    Err(DEVTOOLS_UNSUPPORTED.to_string())
+  }
+  // 关闭调试工具
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn close_devtools(&self) -> Result<(), String> {
+    self.webview.close_devtools();
+    Ok(())
+  }
+  #[cfg(not(any(debug_assertions, feature = "devtools")))]
+  pub fn close_devtools(&self) -> Result<(), String> {
+    Err(DEVTOOLS_UNSUPPORTED.to_string())
+  }
+  // 调试工具是否打开
+  #[cfg(any(debug_assertions, feature = "devtools"))]
+  pub fn is_devtools_open(&self) -> Result<bool, String> {
+    Ok(self.webview.is_devtools_open())
+  }
+  #[cfg(not(any(debug_assertions, feature = "devtools")))]
+  pub fn is_devtools_open(&self) -> Result<bool, String> {
+    Err(DEVTOOLS_UNSUPPORTED.to_string())
+  }
   // 设置webview缩放级别
   pub fn zoom(&self, scale_factor: f64) {
     let _ = self.webview.zoom(scale_factor);
@@ -77,6 +153,18 @@ impl Window{
   pub fn clear_all_browsing_data(&self) {
     let _ = self.webview.clear_all_browsing_data();
   }
+  // 获取cookie，传入url时只返回该url对应的cookie
+  pub fn get_cookies(&self, url: Option<&str>) -> Result<Vec<Cookie<'static>>, String> {
+    self.webview.cookies(url)
+  }
+  // 设置cookie
+  pub fn set_cookie(&self, cookie: &Cookie) -> Result<(), String> {
+    self.webview.set_cookie(cookie)
+  }
+  // 删除cookie
+  pub fn delete_cookie(&self, cookie: &Cookie) -> Result<(), String> {
+    self.webview.delete_cookie(cookie)
+  }
   // 设置背景色
   pub fn set_background_color(&self, color: (u8, u8, u8, u8)) {
     let _ = self.webview.set_background_color(color);
@@ -91,7 +179,7 @@ impl Window{
   }
   // 设置窗口左上角相对于桌面左上角的位置
   pub fn set_outer_position<P: Into<Position>>(&self, position: P){
-    self.window.set_outer_position(position)
+    self.window.set_outer_position(position.into())
   }
   // 返回窗口客户端区域的物理大小(不包括标题栏和边框)
   pub fn inner_size(&self) -> PhysicalSize<u32> {
@@ -99,7 +187,7 @@ impl Window{
   }
   // 设置窗口客户端区域的物理大小
   pub fn set_inner_size<S: Into<Size>>(&self, size: S) -> Option<PhysicalSize<u32>> {
-    self.window.request_inner_size(size)
+    self.window.request_inner_size(size.into())
   }
   // 返回整个窗口的物理大小
   pub fn outer_size(&self) -> PhysicalSize<u32> {
@@ -107,11 +195,11 @@ impl Window{
   }
   // 设置窗口的最小尺寸
   pub fn set_min_inner_size<S: Into<Size>>(&self, min_size: Option<S>) {
-    self.window.set_min_inner_size(min_size)
+    self.window.set_min_inner_size(min_size.map(Into::into))
   }
   // 设置窗口的最大尺寸
   pub fn set_max_inner_size<S: Into<Size>>(&self, max_size: Option<S>) {
-    self.window.set_max_inner_size(max_size) 
+    self.window.set_max_inner_size(max_size.map(Into::into))
   }
   // 设置窗口标题
   pub fn set_title(&self, title: &str) {
@@ -191,11 +279,8 @@ impl Window{
   }
   // 设置窗口图标
   pub fn set_window_icon(&self, icon_path: String) {
-    let icon_path = Path::new(&icon_path);
-    let icon_image = image::open(icon_path).expect("Failed to load icon");
-    let (width, height) = icon_image.dimensions();
-    let rgba_image = icon_image.to_rgba8();
-    let icon = winit::window::Icon::from_rgba(rgba_image.into_raw(), width, height).unwrap();
+    let (rgba, width, height) = load_rgba_image(&icon_path).expect("Failed to load icon");
+    let icon = winit::window::Icon::from_rgba(rgba, width, height).unwrap();
     self.window.set_window_icon(Some(icon))
   }
   // 窗口聚焦
@@ -228,17 +313,141 @@ impl Window{
   }
   // 在指定的位置显示窗口菜单
   pub fn show_window_menu(&self, position: impl Into<Position>) {
-    self.window.show_window_menu(position);
+    self.window.show_window_menu(position.into());
+  }
+  // 设置光标图标
+  pub fn set_cursor(&self, cursor: impl Into<Cursor>) {
+    self.window.set_cursor(cursor.into());
+  }
+  // 设置光标是否可见
+  pub fn set_cursor_visible(&self, visible: bool) {
+    self.window.set_cursor_visible(visible);
+  }
+  // 设置光标锁定模式
+  pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+    self.window.set_cursor_grab(mode)
+  }
+  // 设置光标在窗口内的位置
+  pub fn set_cursor_position(&self, position: impl Into<Position>) -> Result<(), ExternalError> {
+    self.window.set_cursor_position(position.into())
+  }
+  // 设置窗口是否接收鼠标事件，为false时鼠标事件会穿透到下方的窗口
+  pub fn set_cursor_hittest(&mut self, hittest: bool) -> Result<(), ExternalError> {
+    self.window.set_cursor_hittest(hittest)?;
+    self.cursor_hittest = hittest;
+    Ok(())
+  }
+  // 请求重绘窗口，会触发redrawRequested事件
+  pub fn request_redraw(&self) {
+    self.window.request_redraw();
+  }
+  // 设置是否启用输入法
+  pub fn set_ime_allowed(&self, allowed: bool) {
+    self.window.set_ime_allowed(allowed);
+  }
+  // 设置输入法的用途
+  pub fn set_ime_purpose(&self, purpose: ImePurpose) {
+    self.window.set_ime_purpose(purpose);
+  }
+  // 设置输入法候选框的位置，输入法会避免遮挡该区域
+  pub fn set_ime_cursor_area(&self, position: impl Into<Position>, size: impl Into<Size>) {
+    self.window.set_ime_cursor_area(position.into(), size.into());
   }
   // 获取当前显示器
   pub fn current_monitor(&self) -> Option<MonitorHandle> {
     self.window.current_monitor()
   }
-  // 更改webview的大小
+  // 窗口尺寸变化时按布局更改webview的位置和大小
   pub fn resize(&self, size: Size) {
-    let _ = self.webview.set_bounds(Rect{
-      position: LogicalPosition::new(0.0, 0.0).into(),
-      size
-    });
+    let size = size.to_logical(self.window.scale_factor());
+    let _ = self.webview.set_bounds(self.layout.to_rect(size));
+    for child in &self.children {
+      child.apply_layout(size);
+    }
+  }
+  // 以当前窗口为父窗口的窗口属性，创建的窗口始终位于父窗口之上(Linux上不设置)
+  pub fn owned_window_attributes(&self, window_attr: WindowAttributes) -> WindowAttributes {
+    self.window.owned_window_attributes(window_attr)
+  }
+  // 设置状态文件，文件中已有的状态作为初始状态
+  pub fn set_state_file(&mut self, path: Option<PathBuf>) {
+    *self.state.lock().unwrap() = path.as_deref().and_then(WindowState::load);
+    self.state_file = path;
+  }
+  // 更新内存中的窗口状态，状态停止变化STATE_SAVE_DELAY后由save_state_if_due写入文件
+  pub fn update_state(&self) {
+    if self.state_file.is_none() {
+      return;
+    }
+    let mut state = self.state.lock().unwrap();
+    if let Some(current) = WindowState::capture(self, *state) {
+      *state = Some(current);
+      *self.state_save_at.lock().unwrap() = Some(Instant::now() + STATE_SAVE_DELAY);
+    }
+  }
+  // 到了写入时间时写入状态文件，返回下一次需要写入的时间
+  pub fn save_state_if_due(&self) -> Option<Instant> {
+    let save_at = (*self.state_save_at.lock().unwrap())?;
+    if Instant::now() < save_at {
+      return Some(save_at);
+    }
+    self.save_state();
+    None
+  }
+  // 将窗口状态写入状态文件
+  pub fn save_state(&self) {
+    self.update_state();
+    *self.state_save_at.lock().unwrap() = None;
+    if let (Some(path), Some(state)) = (&self.state_file, *self.state.lock().unwrap()) {
+      if let Err(e) = state.save(path) {
+        println!("保存窗口状态错误: {:?}", e);
+      }
+    }
+  }
+  // 设置窗口是否可以接收输入(仅Windows支持)
+  pub fn set_enable(&self, enabled: bool) {
+    self.window.set_enable(enabled);
+  }
+  // 设置主webview的布局
+  pub fn set_webview_bounds(&mut self, layout: Layout) {
+    self.layout = layout;
+    let _ = self.webview.set_bounds(self.layout.to_rect(self.logical_size()));
+  }
+  // 设置主webview的可见性
+  pub fn set_webview_visible(&mut self, visible: bool) {
+    self.webview_visible = visible;
+    let _ = self.webview.set_visible(visible);
+  }
+  // 返回窗口客户端区域的逻辑大小
+  pub fn logical_size(&self) -> LogicalSize<f64> {
+    self.window.inner_size().to_logical(self.window.scale_factor())
+  }
+  // 根据id获取子webview
+  pub fn child(&self, id: &str) -> Option<&ChildWebview> {
+    self.children.iter().find(|c| c.id == id)
+  }
+  pub fn child_mut(&mut self, id: &str) -> Option<&mut ChildWebview> {
+    self.children.iter_mut().find(|c| c.id == id)
+  }
+  // 添加子webview，新添加的webview位于最上层
+  pub fn add_child(&mut self, child: ChildWebview) {
+    self.children.push(child);
+  }
+  // 移除子webview
+  pub fn remove_child(&mut self, id: &str) -> bool {
+    let len = self.children.len();
+    self.children.retain(|c| c.id != id);
+    self.children.len() != len
+  }
+  // 调整子webview的层级，ids按从下到上排列，未列出的webview保持原有顺序并位于下方
+  // 所有webview都调整成功后才保存新的顺序，保证记录的顺序和实际显示一致
+  pub fn reorder_children(&mut self, ids: &[String]) -> Result<(), String> {
+    let current: Vec<&str> = self.children.iter().map(|c| c.id.as_str()).collect();
+    let order = reorder_indices(&current, ids)?;
+    for i in &order {
+      self.children[*i].bring_to_front()?;
+    }
+    apply_order(&mut self.children, &order);
+    Ok(())
   }
 }
@@ -1,14 +1,19 @@
 import { ChildProcessWithoutNullStreams } from 'child_process'
 import { uid, getBinaryPath } from './utils'
 import { spawn } from 'child_process'
-import { ReceiveMessage } from './types'
+import { createInterface } from 'readline'
+import { ReceiveMessage, AppOptions } from './types'
 import Window from './window'
 
 const IO_CHANNEL_PREFIX = '_ioc:';
+/**应用级消息使用的label，不属于任何窗口 */
+export const APP_LABEL = '@app'
+/**已有实例在运行时，子进程转发启动参数后以该退出码退出 */
+const SECOND_INSTANCE_EXIT_CODE = 2
 
 export default class App {
   /**给窗口发送消息的回调 */
-  callbacks: { [key: string]: Function } = {}
+  callbacks: { [key: string]: { resolve: Function, reject: Function } } = {}
   /**监听窗口事件 */
   listeners: { [label: string]: {
     [key: string]: Function[]
@@ -17,38 +22,70 @@ export default class App {
   windows: { [key: string]: Window } = {}
   /**子进程 */
   childProcess: ChildProcessWithoutNullStreams
+  /**子进程启动中，避免窗口和应用同时初始化时启动多个子进程 */
+  private initializing: Promise<void>
+  /**应用选项，需要在子进程启动前设置 */
+  options: AppOptions = {}
 
-  async init(){
-    if(this.childProcess) return
+  init(){
+    if(!this.initializing) this.initializing = this.spawn()
+    return this.initializing
+  }
+  private async spawn(){
     const path = await getBinaryPath()
-    this.childProcess = spawn(path, [], {})
-    // 监听子进程消息
-    this.childProcess.stdout.on('data', (data) => {
-      let str:string = data.toString()
-      str?.split('\n').forEach((item) => {
-        if (!item) return
-        let msg;
-        try {
-          msg = JSON.parse(item.replace(IO_CHANNEL_PREFIX, ''))
-        } catch (e) {
-          console.error(`响应消息格式错误：${item}`)
-          return
-        }
-        this.handleIoMessage(msg)
-      })
+    this.childProcess = spawn(path, this.getSpawnArgs(), {})
+    // 已有实例在运行时，启动参数已转发给该实例，当前进程直接退出
+    this.childProcess.on('exit', (code) => {
+      if (code === SECOND_INSTANCE_EXIT_CODE) process.exit(0)
+    })
+    // 监听子进程消息，按行读取，较大的消息（如剪贴板图片）会被拆分到多个数据块中
+    createInterface({ input: this.childProcess.stdout, crlfDelay: Infinity }).on('line', (item) => {
+      if (!item) return
+      let msg;
+      try {
+        msg = JSON.parse(item.replace(IO_CHANNEL_PREFIX, ''))
+      } catch (e) {
+        console.error(`响应消息格式错误：${item}`)
+        return
+      }
+      this.handleIoMessage(msg)
     })
     this.childProcess.stderr.on('data', (data) => {
       console.error(`错误：${data.toString()}`)
     })
   }
+  // 子进程的启动参数
+  private getSpawnArgs(){
+    const { singleInstance, urlSchemes = [], args = process.argv.slice(2), mock } = this.options
+    // 模拟模式不处理单实例和url协议
+    if (mock) return ['--mock']
+    if (!singleInstance && !urlSchemes.length) return []
+    const spawnArgs = singleInstance ? ['--single-instance', singleInstance] : []
+    urlSchemes.forEach(scheme => spawnArgs.push('--url-scheme', scheme))
+    return [...spawnArgs, '--', ...args]
+  }
   // 处理子进程消息
   handleIoMessage(msg: ReceiveMessage){
+    if (msg.type === 'appEvent') {
+      const listeners = this.listeners[APP_LABEL]?.[msg.method]??[]
+      listeners.forEach(cb => cb(msg.data))
+      return
+    }
     const instance = this.windows[msg.label]
-    if (!instance) return
+    if (!instance && msg.label !== APP_LABEL) return
     switch (msg.type) {
       case 'response':
         const callback = this.callbacks[msg.id as string]
-        if (callback) callback(msg.data)
+        if (!callback) break
+        delete this.callbacks[msg.id as string]
+        // 带有error字段的响应表示执行失败
+        if (msg.error !== undefined) {
+          const error = new Error(msg.error)
+          // 页面脚本抛出的异常使用页面中的调用栈
+          if (msg.stack) error.stack = msg.stack
+          callback.reject(error)
+        }
+        else callback.resolve(msg.data)
         break
       case 'windowEvent':
         const listeners = this.listeners[msg.label]?.[msg.method]??[]
@@ -56,12 +93,16 @@ export default class App {
         break
     }
   }
+  // 向子进程写入消息
+  writeIoMessage(msg) {
+    this.childProcess.stdin.write(`${IO_CHANNEL_PREFIX}${JSON.stringify(msg)}` + '\n')
+  }
   // 向子进程发送消息
-  sendIoMessage(msg, callback) {
+  sendIoMessage(msg, resolve, reject) {
     const id = uid()
     msg.id = id
-    this.callbacks[id] = callback
-    this.childProcess.stdin.write(`${IO_CHANNEL_PREFIX}${JSON.stringify(msg)}` + '\n')
+    this.callbacks[id] = { resolve, reject }
+    this.writeIoMessage(msg)
   }
   // 添加事件监听
   on(label, event, callback){
@@ -0,0 +1,245 @@
+import {
+  AppMessageMethodKey,
+  AppMessageMethodParams,
+  AppMessageMethodResponse,
+  AppEvent,
+  ControlFlow,
+  TrayAttributes,
+  TrayUpdate,
+  TrayClick,
+  MenuItem,
+  MenuItemUpdate,
+  Shortcut,
+  GlobalShortcut,
+  FileDialogOptions,
+  MessageDialogOptions,
+  ClipboardHtml,
+  NotificationOptions,
+  NotificationAction,
+  AppOptions,
+  SecondInstance,
+  UrlScheme,
+  WindowEvent,
+  MockResponse
+} from './types'
+import App, { APP_LABEL } from './app'
+
+let app: App = globalThis.app = globalThis.app || new App()
+
+/**应用级(不属于任何窗口)的方法和事件 */
+export default class Application {
+  /**
+   * 设置应用选项
+   ** 需要在创建窗口和调用其他方法之前设置
+   */
+  setOptions(options: AppOptions){
+    if (app.childProcess) throw new Error('应用已启动，无法设置选项')
+    Object.assign(app.options, options)
+  }
+  /**监听应用事件 */
+  on<T extends keyof AppEvent>(event:T, callback:(data: AppEvent[T]) => void){
+    return app.on(APP_LABEL, event, callback)
+  }
+  /**监听一次应用事件 */
+  once<T extends keyof AppEvent>(event:T, callback:(data: AppEvent[T]) => void){
+    return app.once(APP_LABEL, event, callback)
+  }
+  /**监听事件循环按ControlFlow设置的间隔唤醒 */
+  onTick(callback:() => void){
+    return this.on('tick', callback)
+  }
+  /**监听托盘图标被点击(Linux不支持) */
+  onTrayClick(callback:(data:TrayClick) => void){
+    return this.on('trayClick', callback)
+  }
+  /**监听托盘图标被双击(仅Windows) */
+  onTrayDoubleClick(callback:(data:TrayClick) => void){
+    return this.on('trayDoubleClick', callback)
+  }
+  /**监听菜单项被点击 */
+  onMenuItemClicked(callback:(data:{ id: string }) => void){
+    return this.on('menuItemClicked', callback)
+  }
+  /**监听全局快捷键被按下，注册时设置了releaseEvent时松开也会触发 */
+  onGlobalShortcut(callback:(data:GlobalShortcut) => void){
+    return this.on('globalShortcut', callback)
+  }
+  /**监听再次启动应用(需要设置singleInstance选项) */
+  onSecondInstance(callback:(data:SecondInstance) => void){
+    return this.on('secondInstance', callback)
+  }
+  /**
+   * 监听通过已注册的url协议打开应用(需要设置urlSchemes选项)
+   ** 需要在创建窗口之前注册，否则可能收不到启动参数中的url
+   ** macOS上url通过系统事件传递，暂不支持
+   */
+  onOpenUrl(callback:(url:string) => void){
+    return this.on('openUrl', callback)
+  }
+  /**监听通知被点击 */
+  onNotificationClicked(callback:(data:{ id: string }) => void){
+    return this.on('notificationClicked', callback)
+  }
+  /**监听通知的按钮被点击 */
+  onNotificationAction(callback:(data:NotificationAction) => void){
+    return this.on('notificationAction', callback)
+  }
+  /**
+   * 设置事件循环的运行模式
+   ** wait 没有事件时休眠(默认)
+   ** poll 没有事件时也持续运行，会占用较多CPU
+   ** waitUntil 每隔interval毫秒唤醒一次并触发tick事件
+   */
+  setControlFlow(controlFlow: ControlFlow) {
+    return this.send('set_control_flow', controlFlow)
+  }
+  /**创建托盘图标 */
+  createTray(tray: TrayAttributes) {
+    return this.send('create_tray', tray)
+  }
+  /**修改托盘图标，值为null时清除对应的属性 */
+  updateTray(tray: TrayUpdate) {
+    return this.send('update_tray', tray)
+  }
+  /**移除托盘图标 */
+  removeTray(id: string) {
+    return this.send('remove_tray', id)
+  }
+  /**
+   * 设置应用菜单，传入null移除
+   ** macOS上显示在屏幕顶部的菜单栏
+   ** Windows上显示在没有单独设置菜单的窗口中
+   */
+  setAppMenu(items: MenuItem[] | null) {
+    return this.send('set_app_menu', items)
+  }
+  /**修改菜单项的文字、可用状态、勾选状态或快捷键，只能修改设置了id的菜单项，所在菜单被替换或移除后会返回错误 */
+  updateMenuItem(item: MenuItemUpdate) {
+    return this.send('update_menu_item', item)
+  }
+  /**
+   * 注册全局快捷键，应用没有焦点时也能触发，返回快捷键id
+   ** 快捷键已被其他程序占用时返回的Promise会被reject
+   */
+  registerShortcut(shortcut: Shortcut) {
+    return this.send('register_shortcut', shortcut)
+  }
+  /**注销全局快捷键 */
+  unregisterShortcut(id: string) {
+    return this.send('unregister_shortcut', id)
+  }
+  /**注销所有全局快捷键 */
+  unregisterAllShortcuts() {
+    return this.send('unregister_all_shortcuts')
+  }
+  /**
+   * 打开文件选择对话框(不属于任何窗口)，取消时返回null
+   ** multiple 为 true 时返回路径数组
+   */
+  openFileDialog(options: FileDialogOptions = {}) {
+    return this.send('open_file_dialog', options)
+  }
+  /**打开文件保存对话框(不属于任何窗口)，返回保存路径，取消时返回null */
+  saveFileDialog(options: FileDialogOptions = {}) {
+    return this.send('save_file_dialog', options)
+  }
+  /**
+   * 打开文件夹选择对话框(不属于任何窗口)，取消时返回null
+   ** multiple 为 true 时返回路径数组
+   */
+  pickFolder(options: FileDialogOptions = {}) {
+    return this.send('pick_folder', options)
+  }
+  /**
+   * 显示消息对话框(不属于任何窗口)，返回点击的按钮
+   ** 返回 ok、cancel、yes、no 或自定义按钮的文字
+   */
+  messageDialog(options: MessageDialogOptions) {
+    return this.send('message_dialog', options)
+  }
+  /**读取剪贴板中的纯文本，没有时返回null */
+  readClipboardText() {
+    return this.send('read_clipboard_text')
+  }
+  /**写入纯文本到剪贴板 */
+  writeClipboardText(text: string) {
+    return this.send('write_clipboard_text', text)
+  }
+  /**读取剪贴板中的HTML，没有时返回null */
+  readClipboardHtml() {
+    return this.send('read_clipboard_html')
+  }
+  /**
+   * 写入HTML到剪贴板
+   ** altText 为不支持HTML的程序粘贴时使用的纯文本
+   */
+  writeClipboardHtml(html: string, altText?: string) {
+    const data: ClipboardHtml = { html }
+    if (altText !== undefined) data.altText = altText
+    return this.send('write_clipboard_html', data)
+  }
+  /**
+   * 读取剪贴板中的图片，没有时返回null
+   ** 返回PNG图片的Buffer
+   */
+  async readClipboardImage() {
+    const png = await this.send('read_clipboard_image')
+    return png === null ? null : Buffer.from(png, 'base64')
+  }
+  /**写入PNG图片到剪贴板 */
+  writeClipboardImage(png: Buffer | Uint8Array) {
+    return this.send('write_clipboard_image', Buffer.from(png).toString('base64'))
+  }
+  /**清空剪贴板 */
+  clearClipboard() {
+    return this.send('clear_clipboard')
+  }
+  /**
+   * 发送桌面通知，返回通知id
+   ** Linux上通过freedesktop的D-Bus通知接口发送
+   ** 点击通知触发notificationClicked事件，点击按钮触发notificationAction事件
+   */
+  notify(notification: NotificationOptions) {
+    return this.send('notify', notification)
+  }
+  /**
+   * 注册url协议(仅Linux)，写入.desktop文件并设置为该协议的默认程序
+   ** Windows和macOS上需要在安装包中注册
+   */
+  registerUrlScheme(urlScheme: UrlScheme) {
+    // Exec的引号规则，.desktop字符串层面的反斜杠和%的转义由原生端处理
+    const quote = (arg: string) => `"${arg.replace(/(["`$\\])/g, '\\$1')}"`
+    const exec = urlScheme.exec ?? [process.execPath, process.argv[1]].filter(Boolean).map(quote).join(' ')
+    return this.send('register_url_scheme', { ...urlScheme, exec })
+  }
+  /**删除注册的url协议(仅Linux) */
+  unregisterUrlScheme(scheme: string) {
+    return this.send('unregister_url_scheme', scheme)
+  }
+  /**模拟窗口事件(仅模拟模式) */
+  mockEmitWindowEvent<T extends keyof WindowEvent>(label: string, method: T, data?: WindowEvent[T]) {
+    return this.send('mock_emit_window_event', { label, method, data })
+  }
+  /**模拟应用事件(仅模拟模式) */
+  mockEmitAppEvent<T extends keyof AppEvent>(method: T, data?: AppEvent[T]) {
+    return this.send('mock_emit_app_event', { method, data })
+  }
+  /**
+   * 预设下一次调用方法时的响应(仅模拟模式)
+   ** 用于模拟对话框、右键菜单、脚本执行结果等需要用户操作或页面的响应
+   */
+  mockSetResponse(response: MockResponse) {
+    return this.send('mock_set_response', response)
+  }
+  /**获取所有窗口和应用的状态(仅模拟模式) */
+  mockGetState() {
+    return this.send('mock_get_state')
+  }
+  /**发送消息 */
+  private async send<T extends AppMessageMethodKey>(method: T, data?: AppMessageMethodParams<T>): Promise<AppMessageMethodResponse<T> extends never ? void : AppMessageMethodResponse<T>> {
+    await app.init()
+    return new Promise((resolve, reject) => {
+      app.sendIoMessage({ method, data, label: APP_LABEL }, resolve, reject)
+    })
+  }
+}
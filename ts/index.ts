@@ -1,4 +1,8 @@
 import Window from './window'
+import Application from './application'
+
+/**应用级(不属于任何窗口)的方法和事件 */
+export const application = new Application()
 
 /**根据label获取窗口 */
 export const getWindow = (label: string):Window|undefined => {
@@ -6,5 +10,6 @@ export const getWindow = (label: string):Window|undefined => {
 }
 
 export {
-  Window
+  Window,
+  Application
 }
\ No newline at end of file
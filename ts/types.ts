@@ -14,6 +14,60 @@ export type Monitor = {
   height: number
   scaleFactor: number
 }
+/**请求头 */
+export type Headers = { [name: string]: string }
+/**代理配置 */
+export type ProxyConfig = {
+  /**http为HTTP CONNECT代理，socks5为SOCKSv5代理 */
+  type: 'http' | 'socks5'
+  host: string
+  port: number
+}
+/**开始下载 */
+export type DownloadStarted = {
+  /**下载id */
+  id: string
+  url: string
+  /**建议的文件名 */
+  suggestedFilename: string
+  /**保存路径，取消时为null */
+  path: string | null
+  /**是否按下载策略接受了下载 */
+  accepted: boolean
+}
+/**
+ * 窗口的下载策略，开始下载时直接按策略处理
+ ** action 默认的处理方式，默认 accept
+ ** directory 保存目录，设置后使用建议的文件名保存到该目录
+ ** extensions 按扩展名设置的处理方式，优先于action，例如 { exe: 'cancel' }
+ */
+export type DownloadPolicy = {
+  action?: 'accept' | 'cancel'
+  directory?: string
+  extensions?: { [ext: string]: 'accept' | 'cancel' }
+}
+/**下载完成 */
+export type DownloadCompleted = {
+  /**下载id，和downloadStarted的id相同 */
+  id: string | null
+  url: string
+  /**保存路径 */
+  path: string | null
+  /**是否下载成功 */
+  success: boolean
+}
+/**webview的cookie */
+export type Cookie = {
+  name: string
+  value: string
+  domain?: string | null
+  path?: string | null
+  /**过期时间(unix时间戳，单位秒)，null表示会话cookie */
+  expires?: number | null
+  secure?: boolean
+  httpOnly?: boolean
+  sameSite?: 'strict' | 'lax' | 'none' | null
+}
 export type WindowButton = 'close' | 'minimize' | 'maximize'
 export type Theme = 'light' | 'dark'
 export type WindowLevel = 'normal' | 'alwaysOnTop' | 'alwaysOnBottom'
@@ -31,9 +85,120 @@ export type WindowLevel = 'normal' | 'alwaysOnTop' | 'alwaysOnBottom'
 */
 export type UserAttentionType = 'critical' | 'informational'
 
+/**光标图标(与css的cursor值相同) */
+export type CursorIcon = 'default' | 'context-menu' | 'help' | 'pointer' | 'progress' | 'wait' | 'cell' | 'crosshair' |
+  'text' | 'vertical-text' | 'alias' | 'copy' | 'move' | 'no-drop' | 'not-allowed' | 'grab' | 'grabbing' |
+  'e-resize' | 'n-resize' | 'ne-resize' | 'nw-resize' | 's-resize' | 'se-resize' | 'sw-resize' | 'w-resize' |
+  'ew-resize' | 'ns-resize' | 'nesw-resize' | 'nwse-resize' | 'col-resize' | 'row-resize' | 'all-scroll' |
+  'zoom-in' | 'zoom-out' | 'dnd-ask' | 'all-resize'
+/**自定义光标 */
+export type CustomCursor = {
+  /**图片路径 */
+  path: string
+  /**热点(光标点击位置)相对图片左上角的x坐标 */
+  hotspotX?: number
+  /**热点(光标点击位置)相对图片左上角的y坐标 */
+  hotspotY?: number
+}
+/**
+ * 光标锁定模式
+ ** none 不锁定
+ ** confined 光标被限制在窗口内(macOS不支持)
+ ** locked 光标被锁定在当前位置(Windows不支持)
+ */
+export type CursorGrabMode = 'none' | 'confined' | 'locked'
+
+/**
+ * 输入法的用途
+ ** normal 普通输入
+ ** password 密码输入，输入法应隐藏输入内容
+ ** terminal 终端输入
+ */
+export type ImePurpose = 'normal' | 'password' | 'terminal'
+/**输入法候选框的位置，输入法会避免遮挡该区域 */
+export type ImeCursorArea = {
+  position: Position
+  size: Size
+}
+/**输入法预编辑文本 */
+export type ImePreedit = {
+  text: string
+  /**光标起始位置(文本中的字节偏移)，不显示光标时为null */
+  cursorStart: number | null
+  /**光标结束位置(文本中的字节偏移)，不显示光标时为null */
+  cursorEnd: number | null
+}
+
 /**窗口调整尺寸的方向 */
 export type ResizeDirection = 'east' | 'north' | 'northEast' | 'northWest' | 'south' | 'southEast' | 'southWest' | 'west'
 
+/**
+ * 长度
+ ** 数字为逻辑像素
+ ** '50%' 形式的字符串为相对窗口尺寸的百分比
+ */
+export type Length = number | `${number}%`
+/**
+ * webview的位置和尺寸，类似css的绝对定位
+ ** x/y 为距离窗口左/上边的距离，right/bottom 为距离窗口右/下边的距离
+ ** 未设置宽高时由两边的距离撑开，全部不设置时占满窗口
+ */
+export type WebviewBounds = {
+  x?: Length
+  y?: Length
+  width?: Length
+  height?: Length
+  right?: Length
+  bottom?: Length
+}
+/**webview距离窗口四边的距离 */
+export type WebviewInset = {
+  top?: Length
+  right?: Length
+  bottom?: Length
+  left?: Length
+}
+/**添加子webview的参数 */
+export type ChildWebviewAttributes = Pick<WindowAttributes,
+  'url' | 'html' | 'backgroundColor' | 'transparent' | 'devtools' | 'autoplay' |
+  'userAgent' | 'headers' | 'proxy' | 'dataDirectory' | 'incognito'
+> & {
+  /**webview唯一标识(同一窗口内唯一) */
+  id: string
+  /**位置和尺寸，默认占满窗口 */
+  bounds?: WebviewBounds
+  /**是否显示，默认 true */
+  visible?: boolean
+}
+/**子webview信息(位置和尺寸为逻辑像素) */
+export type WebviewInfo = Position & Size & {
+  id: string
+  visible: boolean
+  url: string | null
+}
+
+/**页面元素信息(位置和尺寸为相对于webview的css像素) */
+export interface ElementInfo {
+  /**小写的标签名 */
+  tagName: string
+  /**元素的文本(innerText) */
+  text: string
+  /**输入框等表单元素的值，其他元素为null */
+  value: string | null
+  /**元素是否可见(尺寸不为0且没有被隐藏) */
+  visible: boolean
+  rect: Position & Size
+  attributes: { [name: string]: string }
+}
+
+/**等待元素的选项 */
+export interface WaitForSelectorOptions {
+  /**超时时间(毫秒)，必须大于0，默认 30000 */
+  timeout?: number
+  /**是否等待元素可见，默认 false */
+  visible?: boolean
+}
+
 /**创建窗口的参数 */
 export interface WindowAttributes {
   /**webview加载的URL(如需加载本地html文件，需使用加上 file: 前缀) */
@@ -46,6 +211,20 @@ export interface WindowAttributes {
   devtools?: boolean
   /**是否运行媒体自动播放 */
   autoplay?: boolean
+  /**webview的User-Agent */
+  userAgent?: string
+  /**加载url时附带的请求头 */
+  headers?: Headers
+  /**webview使用的代理(macOS需要14.0+并开启mac-proxy特性) */
+  proxy?: ProxyConfig
+  /**webview距离窗口四边的距离，用于给原生区域预留空间，窗口尺寸变化时保持不变 */
+  webviewInset?: WebviewInset
+  /**下载策略，窗口创建时生效，之后可以通过setDownloadPolicy修改 */
+  downloadPolicy?: DownloadPolicy
+  /**webview数据目录(cookie、localStorage、缓存等)，使用相同目录的窗口共享浏览数据(macOS上按路径区分数据存储，需要macOS 14+) */
+  dataDirectory?: string
+  /**是否以隐身模式打开webview(不持久化任何浏览数据) */
+  incognito?: boolean
   /**窗口尺寸(不包含标题栏和边框) */
   innerSize?: Size
   /**窗口最小尺寸 */
@@ -85,6 +264,48 @@ export interface WindowAttributes {
   windowLevel?: WindowLevel
   /**是否处于活动状态 */
   active?: boolean
+  /**
+   * 保存窗口位置、尺寸、最大化和全屏状态的文件路径
+   ** 移动或缩放停止500毫秒后和窗口关闭时自动写入(包括用户关闭和调用close)
+   */
+  stateFile?: string
+  /**
+   * 创建窗口时是否从stateFile还原上次的状态，默认 true
+   ** 上次的位置不在当前任何显示器内时只还原尺寸
+   ** 会覆盖 innerSize、position、maximized、fullscreen
+   */
+  restoreState?: boolean
+  /**
+   * 窗口是否接收鼠标事件，默认 true
+   ** 为 false 时鼠标事件会穿透到下方的窗口，可用于悬浮提示等覆盖窗口
+   */
+  cursorHittest?: boolean
+  /**
+   * 是否允许页面通过 ipc 设置鼠标穿透，默认 false
+   ** 开启后页面可以调用 window.nodeWinitWry.setCursorHittest(hittest) 切换鼠标穿透
+   ** 或调用 window.nodeWinitWry.setHittestRegions(regions) 设置可点击区域，光标不在区域内时鼠标穿透
+   ** regions 为元素或 {x, y, width, height} 矩形(相对于webview的css像素)的数组，传入 null 取消
+   ** 可点击区域仅 Windows 支持，其他平台调用 setHittestRegions 会抛出错误，需要页面自行调用 setCursorHittest
+   ** 未开启时忽略页面发送的鼠标穿透消息，子webview的消息始终忽略
+   */
+  hittestRegions?: boolean
+  /**
+   * 窗口菜单栏(仅Windows)，未设置时使用应用菜单
+   ** macOS 只有应用菜单，请使用 application.setAppMenu
+   */
+  menu?: MenuItem[]
+  /**
+   * 父窗口的label
+   ** 窗口始终位于父窗口之上，父窗口关闭时一起关闭
+   ** Linux 上只会随父窗口一起关闭
+   */
+  parent?: string
+  /**
+   * 是否是父窗口的模态窗口(需要设置parent，仅Windows支持)
+   ** 父窗口在该窗口关闭前无法接收输入
+   ** macOS/Linux 上设置为true会创建失败
+   */
+  modal?: boolean
   /**是否全屏
    ** 传入true在当前显示器全屏
    ** 传入显示器id则在指定显示器上全屏(显示器id不存在则在当前显示器全屏)
@@ -98,10 +319,18 @@ export interface MessageMethod {
   set_url: {
     params: string
   }
+  /**使用自定义请求头加载url */
+  load_url_with_headers: {
+    params: { url: string, headers: Headers }
+  }
   /**获取url */
   url: {
     response: string
   }
+  /**设置窗口的下载策略，null为清除 */
+  set_download_policy: {
+    params: DownloadPolicy | null
+  }
   /**在webview上执行js代码 */
   evaluate_script: {
     params: string
@@ -111,6 +340,11 @@ export interface MessageMethod {
     params: string
     response: string
   }
+  /**在webview上执行js代码，等待返回的promise完成并返回结果 */
+  evaluate: {
+    params: { script: string, timeout?: number }
+    response: any
+  }
   /**打开调试工具 */
   open_devtools: {}
   /**关闭调试工具 */
@@ -129,6 +363,19 @@ export interface MessageMethod {
   }
   /**清除所有浏览数据 */
   clear_all_browsing_data: {}
+  /**获取cookie */
+  get_cookies: {
+    params: string | null
+    response: Cookie[]
+  }
+  /**设置cookie */
+  set_cookie: {
+    params: Cookie
+  }
+  /**删除cookie */
+  delete_cookie: {
+    params: Pick<Cookie, 'name' | 'domain' | 'path'>
+  }
   /**设置webview背景色 */
   set_background_color: {
     params: [number, number, number, number]
@@ -293,6 +540,124 @@ export interface MessageMethod {
   show_window_menu: {
     params: Position
   }
+  /**设置webview的位置和尺寸 */
+  set_webview_bounds: {
+    params: WebviewBounds
+  }
+  /**设置webview的可见性 */
+  set_webview_visible: {
+    params: boolean
+  }
+  /**设置光标图标 */
+  set_cursor_icon: {
+    params: CursorIcon
+  }
+  /**设置自定义光标 */
+  set_custom_cursor: {
+    params: CustomCursor
+  }
+  /**设置窗口菜单栏 */
+  set_menu: {
+    params: MenuItem[] | null
+  }
+  /**在窗口内显示右键菜单，返回选中的菜单项id，未选择时为null */
+  show_context_menu: {
+    params: { items: MenuItem[], position?: Position }
+    response: string | null
+  }
+  /**打开文件选择对话框，取消时返回null */
+  open_file_dialog: {
+    params: FileDialogOptions
+    response: string | string[] | null
+  }
+  /**打开文件保存对话框，取消时返回null */
+  save_file_dialog: {
+    params: FileDialogOptions
+    response: string | null
+  }
+  /**打开文件夹选择对话框，取消时返回null */
+  pick_folder: {
+    params: FileDialogOptions
+    response: string | string[] | null
+  }
+  /**显示消息对话框，返回点击的按钮 */
+  message_dialog: {
+    params: MessageDialogOptions
+    response: string
+  }
+  /**请求重绘窗口 */
+  request_redraw: {}
+  /**设置是否启用输入法 */
+  set_ime_allowed: {
+    params: boolean
+  }
+  /**设置输入法的用途 */
+  set_ime_purpose: {
+    params: ImePurpose
+  }
+  /**设置输入法候选框的位置 */
+  set_ime_cursor_area: {
+    params: ImeCursorArea
+  }
+  /**设置窗口是否接收鼠标事件 */
+  set_cursor_hittest: {
+    params: boolean
+  }
+  /**设置光标是否可见 */
+  set_cursor_visible: {
+    params: boolean
+  }
+  /**设置光标锁定模式 */
+  set_cursor_grab: {
+    params: CursorGrabMode
+  }
+  /**设置光标在窗口内的位置 */
+  set_cursor_position: {
+    params: Position
+  }
+  /**添加子webview */
+  add_webview: {
+    params: ChildWebviewAttributes
+  }
+  /**移除子webview */
+  remove_webview: {
+    params: string
+  }
+  /**调整子webview的层级 */
+  reorder_webviews: {
+    params: string[]
+  }
+  /**修改子webview */
+  update_webview: {
+    params: { id: string, bounds?: WebviewBounds, visible?: boolean, url?: string }
+  }
+  /**获取子webview列表 */
+  webviews: {
+    response: WebviewInfo[]
+  }
+  /**查找元素 */
+  find_element: {
+    params: { selector: string }
+    response: ElementInfo | null
+  }
+  /**点击元素 */
+  click_element: {
+    params: { selector: string }
+  }
+  /**在元素中输入文本 */
+  type_text: {
+    params: { selector: string, text: string, clear?: boolean }
+  }
+  /**获取元素的文本 */
+  get_element_text: {
+    params: { selector: string }
+    response: string
+  }
+  /**等待元素出现 */
+  wait_for_selector: {
+    params: WaitForSelectorOptions & { selector: string }
+    response: ElementInfo
+  }
 }
 /**窗口触发的事件 */
 export interface WindowEvent {
@@ -319,10 +684,382 @@ export interface WindowEvent {
   occluded: boolean
   /**窗口大小变更 */
   resize: Size
+  /**开始下载(是否下载和保存路径由下载策略决定) */
+  downloadStarted: DownloadStarted
+  /**下载完成 */
+  downloadCompleted: DownloadCompleted
+  /**输入法启用 */
+  imeEnabled: void
+  /**输入法预编辑文本变更，文本为空表示清除预编辑 */
+  imePreedit: ImePreedit
+  /**输入法提交文本 */
+  imeCommit: string
+  /**输入法禁用 */
+  imeDisabled: void
+  /**窗口需要重绘(调用requestRedraw或系统要求重绘时触发) */
+  redrawRequested: void
+}
+/**
+ * 事件循环的运行模式
+ ** wait 没有事件时休眠(默认)
+ ** poll 没有事件时也持续运行，会占用较多CPU
+ ** waitUntil 每隔interval毫秒唤醒一次并触发tick事件
+ */
+export type ControlFlow = { mode: 'wait' | 'poll' } | { mode: 'waitUntil', interval: number }
+/**
+ * 菜单项
+ ** normal 普通菜单项，点击时触发menuItemClicked事件
+ ** check 可勾选的菜单项
+ ** separator 分隔线
+ ** submenu 子菜单，设置了items时默认为submenu
+ ** predefined 系统预定义的菜单项，由item指定
+ */
+export type MenuItem = {
+  type?: 'normal' | 'check' | 'separator' | 'submenu' | 'predefined'
+  /**菜单项id，menuItemClicked事件返回该id，不设置时自动生成 */
+  id?: string
+  text?: string
+  /**是否可用，默认 true */
+  enabled?: boolean
+  /**是否勾选(仅check) */
+  checked?: boolean
+  /**快捷键(仅normal和check)，如 CmdOrCtrl+Shift+S */
+  accelerator?: string
+  /**预定义菜单项(仅predefined) */
+  item?: PredefinedMenuItem
+  /**子菜单项(仅submenu) */
+  items?: MenuItem[]
+}
+/**系统预定义的菜单项，部分菜单项只在macOS上有效 */
+export type PredefinedMenuItem = 'separator' | 'copy' | 'cut' | 'paste' | 'selectAll' | 'undo' | 'redo' | 'minimize' | 'maximize' | 'fullscreen' | 'hide' | 'hideOthers' | 'showAll' | 'closeWindow' | 'quit' | 'about' | 'services' | 'bringAllToFront'
+/**修改菜单项，只能修改设置了id的菜单项 */
+export type MenuItemUpdate = {
+  id: string
+  text?: string
+  enabled?: boolean
+  /**仅check菜单项 */
+  checked?: boolean
+  /**快捷键，为null时移除 */
+  accelerator?: string | null
+}
+/**托盘图标属性 */
+export type TrayAttributes = {
+  /**托盘图标的唯一标识 */
+  id: string
+  /**图标路径 */
+  icon?: string
+  /**鼠标悬停时的提示 */
+  tooltip?: string
+  /**显示在图标旁边的标题(仅macOS和Linux) */
+  title?: string
+  /**点击图标弹出的菜单 */
+  menu?: MenuItem[]
+  /**左键点击时是否弹出菜单，默认 true(Linux不支持) */
+  menuOnLeftClick?: boolean
+}
+/**修改托盘图标的属性，值为null时清除对应的属性 */
+export type TrayUpdate = {
+  id: string
+  icon?: string | null
+  tooltip?: string | null
+  title?: string | null
+  menu?: MenuItem[] | null
+  menuOnLeftClick?: boolean
+  visible?: boolean
+}
+/**托盘图标点击事件(Linux不支持) */
+export type TrayClick = {
+  /**托盘图标id */
+  id: string
+  /**点击位置(物理像素) */
+  x: number
+  y: number
+  button: 'left' | 'right' | 'middle'
+}
+/**文件过滤器 */
+export type FileFilter = {
+  /**过滤器名称，如 图片 */
+  name: string
+  /**扩展名(不带.)，如 ['png', 'jpg'] */
+  extensions: string[]
+}
+/**文件对话框选项 */
+export type FileDialogOptions = {
+  title?: string
+  /**默认路径，为目录时打开该目录，为文件时打开所在目录并填入文件名 */
+  defaultPath?: string
+  filters?: FileFilter[]
+  /**是否可以多选(仅openFileDialog和pickFolder)，多选时返回路径数组 */
+  multiple?: boolean
+  /**是否可以新建文件夹(仅macOS) */
+  canCreateDirectories?: boolean
+}
+/**
+ * 消息对话框的按钮
+ ** 可以传入1-3个自定义按钮文字，选择时返回按钮文字(Windows需要启用common-controls-v6)
+ */
+export type MessageButtons = 'ok' | 'okCancel' | 'yesNo' | 'yesNoCancel' | string[]
+/**消息对话框选项 */
+export type MessageDialogOptions = {
+  title?: string
+  message: string
+  level?: 'info' | 'warning' | 'error'
+  /**默认 ok */
+  buttons?: MessageButtons
+}
+/**写入剪贴板的HTML */
+export type ClipboardHtml = {
+  html: string
+  /**不支持HTML的程序粘贴时使用的纯文本 */
+  altText?: string
+}
+/**应用选项 */
+export type AppOptions = {
+  /**
+   * 单实例的应用id，设置后同一id只能运行一个实例
+   ** 再次启动时启动参数和工作目录转发给已运行的实例(触发secondInstance事件)，然后退出
+   */
+  singleInstance?: string
+  /**
+   * 应用处理的url协议，如 ['myapp']
+   ** 启动参数中的 myapp://... 会触发openUrl事件，启用单实例时再次启动的参数中的url也会转发给已运行的实例
+   */
+  urlSchemes?: string[]
+  /**应用的启动参数，默认为 process.argv.slice(2) */
+  args?: string[]
+  /**
+   * 模拟模式，不创建真实的窗口和webview，用于在没有显示器的环境(如CI)中测试
+   ** 窗口状态记录在内存中，可以通过mock开头的方法模拟事件和预设响应
+   */
+  mock?: boolean
+}
+/**模拟模式下预设的响应 */
+export type MockResponse = {
+  /**窗口label，不设置时为应用级方法 */
+  label?: string
+  method: string
+  /**响应的数据 */
+  data?: any
+  /**设置后响应为错误 */
+  error?: string
+}
+/**模拟模式下的窗口状态 */
+export type MockWindowState = {
+  id: string
+  parent: string | null
+  /**窗口和主webview的状态，key和创建窗口的参数相同，如 title、innerSize、visible、url、cookies、menu，另有 focused、minimized、webviewBounds */
+  state: { [key: string]: any }
+  /**执行过的脚本 */
+  scripts: string[]
+  webviews: WebviewInfo[]
+}
+/**模拟模式下的应用状态 */
+export type MockState = {
+  windows: { [label: string]: MockWindowState }
+  /**应用级状态：controlFlow、appMenu、clipboardText、clipboardHtml、clipboardImage、urlSchemes */
+  state: { [key: string]: any }
+  /**托盘状态，key和创建托盘的参数相同，另有 visible */
+  trays: { [id: string]: { [key: string]: any } }
+  /**快捷键id对应的快捷键，为规范化后的写法，如 alt+Space */
+  shortcuts: { [id: string]: string }
+  /**发送过的通知，未设置的字段为null */
+  notifications: Required<Omit<NotificationOptions, 'urgency'>>[]
+}
+/**注册url协议(仅Linux) */
+export type UrlScheme = {
+  /**协议名，如 myapp，只能包含字母、数字和+.-，并以字母开头 */
+  scheme: string
+  /**应用名称，默认为协议名，不能包含换行等控制字符 */
+  name?: string
+  /**
+   * 启动应用的命令，url会追加在命令后面，默认为当前node进程和入口脚本
+   ** 包含空格等字符的参数需要按.desktop文件Exec的规则加引号，引号内的 " ` $ \ 需要用反斜杠转义
+   ** 写入文件时会自动把 % 转义为 %%、反斜杠再转义一次，不能包含换行等控制字符
+   */
+  exec?: string
+}
+/**再次启动应用的启动参数和工作目录 */
+export type SecondInstance = {
+  args: string[]
+  cwd: string
+}
+/**桌面通知 */
+export type NotificationOptions = {
+  /**通知id，通知事件返回该id，默认自动生成 */
+  id?: string
+  title: string
+  body?: string
+  /**图标路径，Linux上也可以是图标名称，macOS不支持 */
+  icon?: string
+  /**通知的按钮 */
+  actions?: { id: string, label: string }[]
+  /**自动关闭的时间(毫秒)，0为不自动关闭，Linux上最多保留1小时 */
+  timeout?: number
+  /**紧急程度，默认normal，macOS不支持 */
+  urgency?: 'low' | 'normal' | 'critical'
+}
+/**通知的按钮被点击 */
+export type NotificationAction = {
+  id: string
+  /**按钮id */
+  action: string
+}
+/**全局快捷键 */
+export type Shortcut = {
+  /**快捷键id，globalShortcut事件返回该id，默认为accelerator */
+  id?: string
+  /**快捷键，如 CmdOrCtrl+Shift+Space */
+  accelerator: string
+  /**是否在松开按键时也发送globalShortcut事件(state为released)，默认 false，只在按下时发送 */
+  releaseEvent?: boolean
+}
+/**全局快捷键事件 */
+export type GlobalShortcut = {
+  id: string
+  accelerator: string
+  /**按下或松开，只有注册时设置了releaseEvent才会收到released */
+  state: 'pressed' | 'released'
+}
+/**应用级(不属于任何窗口)的消息 */
+export interface AppMessageMethod {
+  /**设置事件循环的运行模式 */
+  set_control_flow: {
+    params: ControlFlow
+  }
+  /**创建托盘图标 */
+  create_tray: {
+    params: TrayAttributes
+  }
+  /**修改托盘图标 */
+  update_tray: {
+    params: TrayUpdate
+  }
+  /**移除托盘图标 */
+  remove_tray: {
+    params: string
+  }
+  /**设置应用菜单 */
+  set_app_menu: {
+    params: MenuItem[] | null
+  }
+  /**修改菜单项 */
+  update_menu_item: {
+    params: MenuItemUpdate
+  }
+  /**注册全局快捷键 */
+  register_shortcut: {
+    params: Shortcut
+    response: string
+  }
+  /**注销全局快捷键 */
+  unregister_shortcut: {
+    params: string
+  }
+  /**注销所有全局快捷键 */
+  unregister_all_shortcuts: {}
+  /**打开文件选择对话框，取消时返回null */
+  open_file_dialog: {
+    params: FileDialogOptions
+    response: string | string[] | null
+  }
+  /**打开文件保存对话框，取消时返回null */
+  save_file_dialog: {
+    params: FileDialogOptions
+    response: string | null
+  }
+  /**打开文件夹选择对话框，取消时返回null */
+  pick_folder: {
+    params: FileDialogOptions
+    response: string | string[] | null
+  }
+  /**显示消息对话框，返回点击的按钮 */
+  message_dialog: {
+    params: MessageDialogOptions
+    response: string
+  }
+  /**读取剪贴板中的纯文本，没有时返回null */
+  read_clipboard_text: {
+    response: string | null
+  }
+  /**写入纯文本到剪贴板 */
+  write_clipboard_text: {
+    params: string
+  }
+  /**读取剪贴板中的HTML，没有时返回null */
+  read_clipboard_html: {
+    response: string | null
+  }
+  /**写入HTML到剪贴板 */
+  write_clipboard_html: {
+    params: ClipboardHtml
+  }
+  /**读取剪贴板中的图片，返回base64编码的PNG，没有时返回null */
+  read_clipboard_image: {
+    response: string | null
+  }
+  /**写入图片到剪贴板，图片为base64编码的PNG */
+  write_clipboard_image: {
+    params: string
+  }
+  /**清空剪贴板 */
+  clear_clipboard: {}
+  /**发送桌面通知，返回通知id */
+  notify: {
+    params: NotificationOptions
+    response: string
+  }
+  /**注册url协议(仅Linux) */
+  register_url_scheme: {
+    params: UrlScheme
+  }
+  /**删除注册的url协议(仅Linux) */
+  unregister_url_scheme: {
+    params: string
+  }
+  /**模拟窗口事件(仅模拟模式) */
+  mock_emit_window_event: {
+    params: { label: string, method: string, data?: any }
+  }
+  /**模拟应用事件(仅模拟模式) */
+  mock_emit_app_event: {
+    params: { method: string, data?: any }
+  }
+  /**预设下一次调用方法时的响应(仅模拟模式) */
+  mock_set_response: {
+    params: MockResponse
+  }
+  /**获取所有窗口和应用的状态(仅模拟模式) */
+  mock_get_state: {
+    response: MockState
+  }
+}
+/**应用级(不属于任何窗口)的事件 */
+export interface AppEvent {
+  /**事件循环按ControlFlow设置的间隔唤醒 */
+  tick: void
+  /**托盘图标被点击 */
+  trayClick: TrayClick
+  /**托盘图标被双击(仅Windows) */
+  trayDoubleClick: TrayClick
+  /**菜单项被点击 */
+  menuItemClicked: { id: string }
+  /**全局快捷键被按下，注册时设置了releaseEvent时松开也会触发 */
+  globalShortcut: GlobalShortcut
+  /**通知被点击 */
+  notificationClicked: { id: string }
+  /**通知的按钮被点击 */
+  notificationAction: NotificationAction
+  /**启用单实例时，再次启动应用的启动参数和工作目录 */
+  secondInstance: SecondInstance
+  /**通过已注册的url协议打开应用 */
+  openUrl: string
 }
 export type MessageMethodKey = keyof MessageMethod
 export type MessageMethodParams<T extends MessageMethodKey> = 'params' extends keyof MessageMethod[T] ? MessageMethod[T]['params'] : never
 export type MessageMethodResponse<T extends MessageMethodKey> = 'response' extends keyof MessageMethod[T] ? MessageMethod[T]['response'] : never
+export type AppMessageMethodKey = keyof AppMessageMethod
+export type AppMessageMethodParams<T extends AppMessageMethodKey> = 'params' extends keyof AppMessageMethod[T] ? AppMessageMethod[T]['params'] : never
+export type AppMessageMethodResponse<T extends AppMessageMethodKey> = 'response' extends keyof AppMessageMethod[T] ? AppMessageMethod[T]['response'] : never
 
 /**向窗口进程发送的消息格式 */
 export interface SendMessage<T extends MessageMethodKey> {
@@ -334,8 +1071,12 @@ export interface SendMessage<T extends MessageMethodKey> {
 /**接受窗口进程发送的消息格式 */
 export interface ReceiveMessage {
   id?: string
-  type: 'response' | 'windowEvent'
+  type: 'response' | 'windowEvent' | 'appEvent'
   method: string
   label: string
   data?: any
+  /**执行失败时的错误信息 */
+  error?: string
+  /**页面脚本抛出异常时页面中的调用栈 */
+  stack?: string
 }
@@ -11,11 +11,28 @@ import {
   Size,
   ResizeDirection,
   WindowEvent,
-  WindowId
+  WindowId,
+  Cookie,
+  Headers,
+  DownloadStarted,
+  DownloadPolicy,
+  DownloadCompleted,
+  ChildWebviewAttributes,
+  CursorIcon,
+  CustomCursor,
+  CursorGrabMode,
+  ImePurpose,
+  ImeCursorArea,
+  ImePreedit,
+  MenuItem,
+  FileDialogOptions,
+  MessageDialogOptions,
+  WebviewBounds,
+  WaitForSelectorOptions
 } from './types'
 import App from './app'
 
-let app = globalThis.app = new App()
+let app: App = globalThis.app = globalThis.app || new App()
 
 export default class Window {
   /**窗口唯一标识 */
@@ -90,6 +107,34 @@ export default class Window {
   onResize(callback:(data:Size) => void){
     return this.on('resize', callback)
   }
+  /**监听开始下载，是否下载和保存路径由下载策略决定 */
+  onDownloadStarted(callback:(data:DownloadStarted) => void){
+    return this.on('downloadStarted', callback)
+  }
+  /**监听下载完成 */
+  onDownloadCompleted(callback:(data:DownloadCompleted) => void){
+    return this.on('downloadCompleted', callback)
+  }
+  /**监听窗口需要重绘(调用requestRedraw或系统要求重绘时触发) */
+  onRedrawRequested(callback:() => void){
+    return this.on('redrawRequested', callback)
+  }
+  /**监听输入法启用 */
+  onImeEnabled(callback:() => void){
+    return this.on('imeEnabled', callback)
+  }
+  /**监听输入法预编辑文本变更，文本为空表示清除预编辑 */
+  onImePreedit(callback:(data:ImePreedit) => void){
+    return this.on('imePreedit', callback)
+  }
+  /**监听输入法提交文本 */
+  onImeCommit(callback:(data:string) => void){
+    return this.on('imeCommit', callback)
+  }
+  /**监听输入法禁用 */
+  onImeDisabled(callback:() => void){
+    return this.on('imeDisabled', callback)
+  }
   /**关闭窗口 */
   close() {
     return this.send('close')
@@ -98,6 +143,17 @@ export default class Window {
   setUrl(url: string) {
     return this.send('set_url', url)
   }
+  /**使用自定义请求头加载url */
+  loadUrlWithHeaders(url: string, headers: Headers) {
+    return this.send('load_url_with_headers', { url, headers })
+  }
+  /**
+   * 设置窗口的下载策略，null为清除
+   ** 开始下载时直接按策略决定是否下载和保存路径，需要在下载开始前设置
+   */
+  setDownloadPolicy(policy: DownloadPolicy | null) {
+    return this.send('set_download_policy', policy)
+  }
   /**获取webview URL */
   url() {
     return this.send('url')
@@ -114,18 +170,29 @@ export default class Window {
   evaluateScriptReturnResult(script: string) {
     return this.send('evaluate_script_with_callback', script)
   }
-  // /**打开调试工具 */
-  // openDevtools() {
-  //   return this.send('open_devtools')
-  // }
-  // /**关闭调试工具 */
-  // closeDevtools() {
-  //   return this.send('close_devtools')
-  // }
-  // /**调试工具是否打开 */
-  // isDevtoolsOpen() {
-  //   return this.send('is_devtools_open')
-  // }
+  /**
+   * 在webview的全局作用域执行js代码，返回最后一个表达式的值  
+   * 返回值为promise时等待promise完成，结果需要可以被JSON序列化  
+   * 脚本抛出异常或promise被拒绝时reject，错误的stack为页面中的调用栈  
+   * 超时后reject，timeout为大于0的毫秒数，默认超时时间为30秒(页面的CSP禁止eval时无法使用)  
+   * 例如  
+   * await window.evaluate('fetch("/api/user").then(res => res.json())') 将返回接口的数据
+   */
+  evaluate<T = any>(script: string, options?: { timeout?: number }): Promise<T> {
+    return this.send('evaluate', { ...options, script })
+  }
+  /**打开调试工具(正式包需要开启devtools特性，否则会reject) */
+  openDevtools() {
+    return this.send('open_devtools')
+  }
+  /**关闭调试工具(Windows不支持) */
+  closeDevtools() {
+    return this.send('close_devtools')
+  }
+  /**调试工具是否打开(Windows不支持) */
+  isDevtoolsOpen() {
+    return this.send('is_devtools_open')
+  }
   /**设置webview缩放等级 */
   zoom(scale: number) {
     return this.send('zoom', scale)
@@ -138,6 +205,18 @@ export default class Window {
   clearAllBrowsingData() {
     return this.send('clear_all_browsing_data')
   }
+  /**获取webview的cookie，传入url时只返回该url对应的cookie */
+  getCookies(url?: string) {
+    return this.send('get_cookies', url ?? null)
+  }
+  /**设置cookie */
+  setCookie(cookie: Cookie) {
+    return this.send('set_cookie', cookie)
+  }
+  /**删除cookie(根据name、domain、path匹配) */
+  deleteCookie(cookie: Pick<Cookie, 'name' | 'domain' | 'path'>) {
+    return this.send('delete_cookie', cookie)
+  }
   /**设置webview背景色(macOS不支持) */
   setBackgroundColor(color: [number, number, number, number]) {
     return this.send('set_background_color', color)
@@ -350,9 +429,166 @@ export default class Window {
   showMenu(position: Position) {
     return this.send('show_window_menu', position)
   }
+  /**设置光标图标 */
+  setCursorIcon(icon: CursorIcon) {
+    return this.send('set_cursor_icon', icon)
+  }
+  /**使用图片设置自定义光标 */
+  setCustomCursor(cursor: CustomCursor) {
+    return this.send('set_custom_cursor', cursor)
+  }
+  /**
+   * 设置窗口菜单栏(仅Windows)，传入null时恢复为应用菜单
+   ** macOS 只有应用菜单，请使用 application.setAppMenu
+   */
+  setMenu(items: MenuItem[] | null) {
+    return this.send('set_menu', items)
+  }
+  /**
+   * 在窗口内显示右键菜单，菜单关闭后返回选中的菜单项id，未选择时为null
+   ** position 为相对窗口客户区左上角的逻辑坐标，不传时显示在光标位置
+   ** 右键菜单的菜单项不会触发menuItemClicked事件
+   */
+  showContextMenu(items: MenuItem[], position?: Position) {
+    return this.send('show_context_menu', { items, position })
+  }
+  /**
+   * 打开文件选择对话框(以当前窗口为父窗口)，取消时返回null
+   ** multiple 为 true 时返回路径数组
+   */
+  openFileDialog(options: FileDialogOptions = {}) {
+    return this.send('open_file_dialog', options)
+  }
+  /**打开文件保存对话框(以当前窗口为父窗口)，返回保存路径，取消时返回null */
+  saveFileDialog(options: FileDialogOptions = {}) {
+    return this.send('save_file_dialog', options)
+  }
+  /**
+   * 打开文件夹选择对话框(以当前窗口为父窗口)，取消时返回null
+   ** multiple 为 true 时返回路径数组
+   */
+  pickFolder(options: FileDialogOptions = {}) {
+    return this.send('pick_folder', options)
+  }
+  /**
+   * 显示消息对话框(以当前窗口为父窗口)，返回点击的按钮
+   ** 返回 ok、cancel、yes、no 或自定义按钮的文字
+   */
+  messageDialog(options: MessageDialogOptions) {
+    return this.send('message_dialog', options)
+  }
+  /**请求重绘窗口，会触发redrawRequested事件 */
+  requestRedraw() {
+    return this.send('request_redraw')
+  }
+  /**
+   * 设置是否启用输入法
+   ** 启用后才会收到imeEnabled、imePreedit、imeCommit、imeDisabled事件
+   */
+  setImeAllowed(allowed: boolean) {
+    return this.send('set_ime_allowed', allowed)
+  }
+  /**设置输入法的用途 */
+  setImePurpose(purpose: ImePurpose) {
+    return this.send('set_ime_purpose', purpose)
+  }
+  /**设置输入法候选框的位置(相对窗口客户区的逻辑像素)，输入法会避免遮挡该区域 */
+  setImeCursorArea(area: ImeCursorArea) {
+    return this.send('set_ime_cursor_area', area)
+  }
+  /**
+   * 设置窗口是否接收鼠标事件
+   ** 为 false 时鼠标事件会穿透到下方的窗口，同时取消页面设置的可点击区域
+   */
+  setCursorHittest(hittest: boolean) {
+    return this.send('set_cursor_hittest', hittest)
+  }
+  /**设置光标是否可见 */
+  setCursorVisible(visible: boolean) {
+    return this.send('set_cursor_visible', visible)
+  }
+  /**
+   * 设置光标锁定模式
+   ** confined 光标被限制在窗口内(macOS不支持)
+   ** locked 光标被锁定在当前位置(Windows不支持)
+   */
+  setCursorGrab(mode: CursorGrabMode) {
+    return this.send('set_cursor_grab', mode)
+  }
+  /**设置光标在窗口内的位置 */
+  setCursorPosition(position: Position) {
+    return this.send('set_cursor_position', position)
+  }
+  /**
+   * 设置webview的位置和尺寸
+   ** 窗口尺寸变化时按设置的布局重新计算，不再自动占满窗口
+   */
+  setWebviewBounds(bounds: WebviewBounds) {
+    return this.send('set_webview_bounds', bounds)
+  }
+  /**设置webview的可见性(不影响窗口，可用于显示启动画面时隐藏webview) */
+  setWebviewVisible(visible: boolean) {
+    return this.send('set_webview_visible', visible)
+  }
+  /**
+   * 在窗口内添加子webview
+   ** 新添加的webview位于最上层
+   */
+  addWebview(attrs: ChildWebviewAttributes) {
+    return this.send('add_webview', attrs)
+  }
+  /**移除子webview */
+  removeWebview(id: string) {
+    return this.send('remove_webview', id)
+  }
+  /**
+   * 调整子webview的层级(Linux不支持)
+   ** ids 按从下到上的顺序排列，未列出的webview保持原有顺序并位于下方
+   */
+  reorderWebviews(ids: string[]) {
+    return this.send('reorder_webviews', ids)
+  }
+  /**修改子webview的位置尺寸、可见性或URL */
+  updateWebview(id: string, props: { bounds?: WebviewBounds, visible?: boolean, url?: string }) {
+    return this.send('update_webview', { ...props, id })
+  }
+  /**获取子webview列表 */
+  webviews() {
+    return this.send('webviews')
+  }
+  /**查找页面中匹配选择器的第一个元素，找不到时返回null */
+  findElement(selector: string) {
+    return this.send('find_element', { selector })
+  }
+  /**
+   * 点击元素
+   ** 滚动到元素可见后在元素中心依次触发pointerdown、mousedown、pointerup、mouseup和click事件
+   */
+  click(selector: string) {
+    return this.send('click_element', { selector })
+  }
+  /**
+   * 在输入框或可编辑元素中逐个字符输入文本
+   ** 每个字符都会触发keydown、keypress、input和keyup事件，输入完成后触发change事件
+   ** clear为true时先清空原有内容
+   */
+  type(selector: string, text: string, options?: { clear?: boolean }) {
+    return this.send('type_text', { ...options, selector, text })
+  }
+  /**获取元素的文本(innerText) */
+  getText(selector: string) {
+    return this.send('get_element_text', { selector })
+  }
+  /**
+   * 等待匹配选择器的元素出现，返回元素信息
+   ** 超时后reject，默认超时时间为30秒
+   */
+  waitForSelector(selector: string, options?: WaitForSelectorOptions) {
+    return this.send('wait_for_selector', { ...options, selector })
+  }
   private send<T extends MessageMethodKey>(method: T, data?: MessageMethodParams<T>): Promise<MessageMethodResponse<T> extends never ? void : MessageMethodResponse<T>> {
     return new Promise((resolve, reject) => {
-      app.sendIoMessage({ method, data, label: this.label }, resolve)
+      app.sendIoMessage({ method, data, label: this.label }, resolve, reject)
     })
   }
 }
\ No newline at end of file